@@ -3,8 +3,9 @@
 use bevy::prelude::*;
 use iyes_loopless::prelude::*;
 
+use shared::components::MainCamera;
 use shared::constants::{GAME_FONT, PLAYER_HEART_EMPTY, PLAYER_HEART_FULL};
-use shared::resources::{AppState, UiTextures, WinSize};
+use shared::resources::{AppState, Difficulty, HighScores, Settings, UiTextures, WinSize};
 
 use stage_1_mainmenu::MainMenuStage;
 use stage_2_gameplay::GameplayStage;
@@ -41,7 +42,9 @@ fn main() {
 }
 
 fn setup_system(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(MainCamera);
     commands.spawn_bundle(UiCameraBundle::default());
 
     // add UiTextures resource
@@ -52,4 +55,7 @@ fn setup_system(mut commands: Commands, asset_server: Res<AssetServer>) {
     };
 
     commands.insert_resource(ui_textures);
+    commands.insert_resource(Settings::default());
+    commands.insert_resource(Difficulty::default());
+    commands.insert_resource(HighScores::load());
 }
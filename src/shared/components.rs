@@ -11,9 +11,20 @@ pub struct GameplayTeardown;
 #[derive(Component)]
 pub struct ResetGameplay;
 
+#[derive(Component)]
+pub struct ContinueGameplay;
+
 #[derive(Component)]
 pub struct SpawnPlayer;
 
+#[derive(Component)]
+pub struct LoadGame;
+
+// Camera components
+
+#[derive(Component)]
+pub struct MainCamera;
+
 // Ui components
 
 #[derive(Component)]
@@ -2,6 +2,14 @@ use bevy::prelude::*;
 
 // Asset Constants
 
+pub const ASSET_FOLDER: &str = "assets";
+
+// Files written next to the assets folder
+pub const SAVE_FILE: &str = "savegame.ron";
+pub const HIGH_SCORES_FILE: &str = "highscores.ron";
+
+pub const HIGH_SCORE_COUNT: usize = 10; // how many scores are kept
+
 pub const PLAYER_HEART_FULL: &str = "hud_heartFull.png";
 pub const PLAYER_HEART_EMPTY: &str = "hud_heartEmpty.png";
 
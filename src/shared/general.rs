@@ -1,6 +1,10 @@
-use bevy::prelude::*;
+use std::fs;
 
-use super::constants::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use bevy::{asset::FileAssetIo, prelude::*};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::constants::{ASSET_FOLDER, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use super::resources::UiTextures;
 
 // Basic button color match
 pub fn button_color_system(
@@ -24,6 +28,58 @@ pub fn button_color_system(
     }
 }
 
+// Menu button as wide as its panel, the marker tells the buttons apart
+pub fn spawn_menu_button(
+    parent: &mut ChildBuilder,
+    ui_textures: &UiTextures,
+    label: impl Into<String>,
+    height: Val,
+    marker: impl Component,
+) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), height),
+                margin: Rect::all(Val::Auto),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: NORMAL_BUTTON.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    label,
+                    TextStyle {
+                        font: ui_textures.ui_font.clone(),
+                        font_size: 40.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                    Default::default(),
+                ),
+                ..default()
+            });
+        })
+        .insert(marker);
+}
+
+// Puts the label returned by the system it's chained to on the buttons with the marker
+pub fn button_label_system<B: Component>(
+    In(label): In<String>,
+    button_query: Query<&Children, With<B>>,
+    mut text_query: Query<&mut Text>,
+) {
+    for children in button_query.iter() {
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = label.clone();
+            }
+        }
+    }
+}
+
 // Despawns all entities that have a specific component attached to it
 pub fn despawn_system<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
     for entity in query.iter() {
@@ -50,3 +106,56 @@ pub fn on_button_interact<B: Component>(
 pub fn esc_pressed(kdb: Res<Input<KeyCode>>) -> bool {
     kdb.just_pressed(KeyCode::Escape)
 }
+
+pub fn f3_pressed(kdb: Res<Input<KeyCode>>) -> bool {
+    kdb.just_pressed(KeyCode::F3)
+}
+
+pub fn grave_pressed(kdb: Res<Input<KeyCode>>) -> bool {
+    kdb.just_pressed(KeyCode::Grave)
+}
+
+// Reads and parses a RON data file from the asset folder
+//
+// Panics when the file is missing or malformed since the game can't run without its data
+pub fn load_asset_data<T: DeserializeOwned>(path: &str) -> T {
+    let full_path = FileAssetIo::get_root_path().join(ASSET_FOLDER).join(path);
+    let data = fs::read_to_string(&full_path)
+        .unwrap_or_else(|err| panic!("Failed to read {}: {}", full_path.display(), err));
+
+    ron::from_str(&data).unwrap_or_else(|err| panic!("Failed to parse {}: {}", path, err))
+}
+
+// Save data lives next to the asset folder, failing to read or write it is never fatal
+pub fn save_exists(file: &str) -> bool {
+    FileAssetIo::get_root_path().join(file).exists()
+}
+
+pub fn write_save_data<T: Serialize>(file: &str, data: &T) {
+    let full_path = FileAssetIo::get_root_path().join(file);
+    let result = ron::ser::to_string_pretty(data, Default::default())
+        .map_err(|err| err.to_string())
+        .and_then(|data| fs::write(&full_path, data).map_err(|err| err.to_string()));
+
+    if let Err(err) = result {
+        error!("Failed to write {}: {}", full_path.display(), err);
+    }
+}
+
+pub fn read_save_data<T: DeserializeOwned>(file: &str) -> Option<T> {
+    let full_path = FileAssetIo::get_root_path().join(file);
+    let data = fs::read_to_string(&full_path).ok()?;
+
+    ron::from_str(&data)
+        .map_err(|err| error!("Failed to parse {}: {}", full_path.display(), err))
+        .ok()
+}
+
+pub fn delete_save_data(file: &str) {
+    let full_path = FileAssetIo::get_root_path().join(file);
+    if full_path.exists() {
+        if let Err(err) = fs::remove_file(&full_path) {
+            error!("Failed to delete {}: {}", full_path.display(), err);
+        }
+    }
+}
@@ -1,4 +1,10 @@
+use std::cmp::Reverse;
+
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::constants::{HIGH_SCORES_FILE, HIGH_SCORE_COUNT};
+use super::general::{read_save_data, write_save_data};
 
 // Resources
 
@@ -13,6 +19,125 @@ pub struct UiTextures {
     pub ui_font: Handle<Font>,
 }
 
+// Continues left in the current run
+pub struct Continues(pub u32);
+
+// Player preferences that persist between games
+pub struct Settings {
+    pub screen_shake: bool,
+    pub adaptive_difficulty: bool,
+    pub controls: ControlScheme,
+    pub fire_mode: FireMode,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            screen_shake: true,
+            adaptive_difficulty: false,
+            controls: ControlScheme::default(),
+            fire_mode: FireMode::default(),
+        }
+    }
+}
+
+// Where the player's ship takes its orders from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ControlScheme {
+    #[default]
+    Keyboard,
+    Pointer, // ship follows the mouse, fires on the left button and charges on the right
+}
+
+// When the keyboard fires, holding the button down charges the weapon unless holding is how it fires
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FireMode {
+    #[default]
+    Tap, // one shot per press
+    Hold, // keeps firing while the button is held
+    Auto, // fires on its own, the button only charges
+}
+
+impl FireMode {
+    pub fn next(&self) -> FireMode {
+        match self {
+            FireMode::Tap => FireMode::Hold,
+            FireMode::Hold => FireMode::Auto,
+            FireMode::Auto => FireMode::Tap,
+        }
+    }
+}
+
+// Difficulty picked on the main menu, applies to the whole run
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Nightmare,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Nightmare => "Nightmare",
+        }
+    }
+
+    // Returns the next harder difficulty, wraps around to the easiest one
+    pub fn next(&self) -> Difficulty {
+        let idx = Difficulty::ALL.iter().position(|d| d == self).unwrap();
+        Difficulty::ALL[(idx + 1) % Difficulty::ALL.len()]
+    }
+}
+
+// Difficulty picked on the main menu while a resumed run plays at the one it was saved with
+pub struct MenuDifficulty(pub Difficulty);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct HighScore {
+    pub score: u32,
+    pub difficulty: Difficulty,
+}
+
+// Best scores of finished runs, highest first
+#[derive(Default, Deserialize, Serialize)]
+pub struct HighScores(pub Vec<HighScore>);
+
+impl HighScores {
+    pub fn load() -> Self {
+        read_save_data(HIGH_SCORES_FILE).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        write_save_data(HIGH_SCORES_FILE, self);
+    }
+
+    pub fn record(&mut self, score: u32, difficulty: Difficulty) {
+        self.0.push(HighScore { score, difficulty });
+        self.0.sort_by_key(|high_score| Reverse(high_score.score));
+        self.0.truncate(HIGH_SCORE_COUNT);
+    }
+
+    pub fn best(&self, difficulty: Difficulty) -> Option<u32> {
+        self.0
+            .iter()
+            .find(|high_score| high_score.difficulty == difficulty)
+            .map(|high_score| high_score.score)
+    }
+}
+
 // Game states
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
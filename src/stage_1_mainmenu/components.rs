@@ -3,5 +3,14 @@ use bevy::prelude::Component;
 #[derive(Component)]
 pub struct GameplayButton;
 
+#[derive(Component)]
+pub struct ResumeButton;
+
+#[derive(Component)]
+pub struct DifficultyButton;
+
+#[derive(Component)]
+pub struct BestScoreText;
+
 #[derive(Component)]
 pub struct MainMenu;
@@ -6,12 +6,16 @@ use iyes_loopless::{
 };
 
 use crate::shared::{
-    constants::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON},
-    general::{button_color_system, despawn_system, on_button_interact},
-    resources::{AppState, UiTextures},
+    components::LoadGame,
+    constants::{HOVERED_BUTTON, PRESSED_BUTTON, SAVE_FILE},
+    general::{
+        button_color_system, button_label_system, delete_save_data, despawn_system,
+        on_button_interact, save_exists, spawn_menu_button,
+    },
+    resources::{AppState, Difficulty, HighScores, MenuDifficulty, UiTextures},
 };
 
-use super::components::{GameplayButton, MainMenu};
+use super::components::{BestScoreText, DifficultyButton, GameplayButton, MainMenu, ResumeButton};
 
 pub struct MainMenuPlugin;
 
@@ -26,6 +30,23 @@ impl Plugin for MainMenuPlugin {
                     .with_system(start_gameplay_system)
                     .into(),
             )
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(AppState::MainMenu)
+                    .run_if(on_button_interact::<ResumeButton>)
+                    .with_system(resume_gameplay_system)
+                    .into(),
+            )
+            // --- Settings ---
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(AppState::MainMenu)
+                    .run_if(on_button_interact::<DifficultyButton>)
+                    .with_system(
+                        cycle_difficulty_system.chain(button_label_system::<DifficultyButton>),
+                    )
+                    .into(),
+            )
             // --- Basic button color changer ---
             .add_system_set(
                 ConditionSet::new()
@@ -38,23 +59,60 @@ impl Plugin for MainMenuPlugin {
     }
 }
 
-fn setup_mainmenu_system(mut commands: Commands, ui_textures: Res<UiTextures>) {
+fn setup_mainmenu_system(
+    mut commands: Commands,
+    ui_textures: Res<UiTextures>,
+    mut difficulty: ResMut<Difficulty>,
+    menu_difficulty: Option<Res<MenuDifficulty>>,
+    high_scores: Res<HighScores>,
+) {
+    // back from a resumed run, the menu shows what was picked before it
+    if let Some(menu_difficulty) = menu_difficulty {
+        *difficulty = menu_difficulty.0;
+        commands.remove_resource::<MenuDifficulty>();
+    }
+
+    // run left through the pause menu can be picked back up
+    let can_resume = save_exists(SAVE_FILE);
+    let buttons = if can_resume { 3. } else { 2. };
+
     commands
         .spawn_bundle(NodeBundle {
             style: Style {
                 size: Size::new(Val::Percent(100.), Val::Percent(100.)),
                 justify_content: JustifyContent::Center,
                 align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
                 ..Default::default()
             },
             color: Color::NONE.into(),
             ..Default::default()
         })
         .with_children(|parent| {
+            // best score of the selected difficulty goes under the buttons
+            parent
+                .spawn_bundle(TextBundle {
+                    style: Style {
+                        margin: Rect::all(Val::Px(10.)),
+                        ..Default::default()
+                    },
+                    text: Text::with_section(
+                        best_score_label(&high_scores, *difficulty),
+                        TextStyle {
+                            font: ui_textures.ui_font.clone(),
+                            font_size: 30.0,
+                            color: Color::rgb(0.9, 0.9, 0.9),
+                        },
+                        Default::default(),
+                    ),
+                    ..default()
+                })
+                .insert(BestScoreText);
+
             parent
                 .spawn_bundle(NodeBundle {
                     style: Style {
-                        size: Size::new(Val::Px(150.), Val::Px(65.)),
+                        size: Size::new(Val::Px(150.), Val::Px(65. * buttons)),
                         border: Rect::all(Val::Px(2.)),
                         ..Default::default()
                     },
@@ -68,39 +126,37 @@ fn setup_mainmenu_system(mut commands: Commands, ui_textures: Res<UiTextures>) {
                                 size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
                                 align_items: AlignItems::Center,
                                 justify_content: JustifyContent::Center,
+                                flex_direction: FlexDirection::Column,
                                 ..Default::default()
                             },
                             color: Color::rgb(0.15, 0.15, 0.15).into(),
                             ..Default::default()
                         })
                         .with_children(|parent| {
-                            parent
-                                .spawn_bundle(ButtonBundle {
-                                    style: Style {
-                                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
-                                        margin: Rect::all(Val::Auto),
-                                        justify_content: JustifyContent::Center,
-                                        align_items: AlignItems::Center,
-                                        ..Default::default()
-                                    },
-                                    color: NORMAL_BUTTON.into(),
-                                    ..Default::default()
-                                })
-                                .with_children(|parent| {
-                                    parent.spawn_bundle(TextBundle {
-                                        text: Text::with_section(
-                                            "Start game",
-                                            TextStyle {
-                                                font: ui_textures.ui_font.clone(),
-                                                font_size: 40.0,
-                                                color: Color::rgb(0.9, 0.9, 0.9),
-                                            },
-                                            Default::default(),
-                                        ),
-                                        ..default()
-                                    });
-                                })
-                                .insert(GameplayButton);
+                            let height = Val::Percent(100. / buttons);
+                            spawn_menu_button(
+                                parent,
+                                &ui_textures,
+                                difficulty.name(),
+                                height,
+                                DifficultyButton,
+                            );
+                            spawn_menu_button(
+                                parent,
+                                &ui_textures,
+                                "Start game",
+                                height,
+                                GameplayButton,
+                            );
+                            if can_resume {
+                                spawn_menu_button(
+                                    parent,
+                                    &ui_textures,
+                                    "Resume",
+                                    height,
+                                    ResumeButton,
+                                );
+                            }
                         });
                 });
         })
@@ -108,5 +164,33 @@ fn setup_mainmenu_system(mut commands: Commands, ui_textures: Res<UiTextures>) {
 }
 
 fn start_gameplay_system(mut commands: Commands) {
+    // a new run drops the one left behind
+    delete_save_data(SAVE_FILE);
     commands.insert_resource(NextState(AppState::Gameplay));
 }
+
+fn resume_gameplay_system(mut commands: Commands) {
+    commands.insert_resource(LoadGame);
+    commands.insert_resource(NextState(AppState::Gameplay));
+}
+
+fn best_score_label(high_scores: &HighScores, difficulty: Difficulty) -> String {
+    match high_scores.best(difficulty) {
+        Some(score) => format!("Best {}", score),
+        None => String::new(),
+    }
+}
+
+fn cycle_difficulty_system(
+    mut difficulty: ResMut<Difficulty>,
+    high_scores: Res<HighScores>,
+    mut best_query: Query<&mut Text, With<BestScoreText>>,
+) -> String {
+    *difficulty = difficulty.next();
+
+    for mut text in best_query.iter_mut() {
+        text.sections[0].value = best_score_label(&high_scores, *difficulty);
+    }
+
+    difficulty.name().to_string()
+}
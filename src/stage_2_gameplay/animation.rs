@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use iyes_loopless::prelude::{AppLooplessStateExt, ConditionSet, IntoConditionalSystem};
+use serde::Deserialize;
+
+use super::components::{Animation, AnimationFinished, DespawnOnFinish};
+use super::constants::ANIMATION_CLIPS;
+use crate::shared::components::GameRunning;
+use crate::shared::general::load_asset_data;
+use crate::shared::resources::AppState;
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AnimationFinished>()
+            .add_enter_system(
+                AppState::Gameplay,
+                animation_library_setup_system.run_unless_resource_exists::<GameRunning>(),
+            )
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(AppState::Gameplay)
+                    .run_if_resource_exists::<AnimationLibrary>()
+                    .with_system(animation_system)
+                    .with_system(despawn_on_finish_system)
+                    .into(),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub enum AnimationMode {
+    Loop,
+    Once,
+}
+
+// Every track is optional, the longest one decides how many steps the clip has
+#[derive(Clone, Debug, Deserialize)]
+pub struct AnimationClip {
+    pub frame_time: f32,
+    #[serde(default)]
+    pub frames: Vec<usize>,
+    #[serde(default)]
+    pub alpha: Vec<f32>,
+    #[serde(default)]
+    pub scale: Vec<f32>,
+    pub mode: AnimationMode,
+}
+
+impl AnimationClip {
+    pub fn len(&self) -> usize {
+        self.frames
+            .len()
+            .max(self.alpha.len())
+            .max(self.scale.len())
+    }
+}
+
+// Resource - every known animation clip by name
+#[derive(Default)]
+pub struct AnimationLibrary(HashMap<String, AnimationClip>);
+
+impl AnimationLibrary {
+    pub fn load() -> Self {
+        AnimationLibrary(load_asset_data(ANIMATION_CLIPS))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AnimationClip> {
+        self.0.get(name)
+    }
+}
+
+fn animation_library_setup_system(mut commands: Commands) {
+    commands.insert_resource(AnimationLibrary::load());
+}
+
+fn animation_system(
+    time: Res<Time>,
+    library: Res<AnimationLibrary>,
+    mut ev_finished: EventWriter<AnimationFinished>,
+    mut query: Query<(
+        Entity,
+        &mut Animation,
+        &mut Transform,
+        Option<&mut Sprite>,
+        Option<&mut TextureAtlasSprite>,
+    )>,
+) {
+    for (entity, mut animation, mut transform, sprite, atlas_sprite) in query.iter_mut() {
+        if animation.finished {
+            continue;
+        }
+
+        let clip = match library.get(&animation.clip) {
+            Some(clip) if clip.len() > 0 => clip,
+            _ => {
+                warn!("Unknown or empty animation clip: {}", animation.clip);
+                animation.finished = true;
+                continue;
+            }
+        };
+
+        // scale track is relative to the scale the entity had when the animation started
+        let base_scale = *animation.base_scale.get_or_insert(transform.scale);
+
+        let timer = animation
+            .timer
+            .get_or_insert_with(|| Timer::from_seconds(clip.frame_time, true));
+        timer.tick(time.delta());
+
+        if timer.just_finished() {
+            let next = animation.step + 1;
+            if next < clip.len() {
+                animation.step = next;
+            } else if clip.mode == AnimationMode::Loop {
+                animation.step = 0;
+            } else {
+                animation.finished = true;
+                ev_finished.send(AnimationFinished {
+                    entity,
+                    clip: animation.clip.clone(),
+                });
+                continue;
+            }
+        }
+
+        // tracks shorter than the clip keep repeating
+        let step = animation.step;
+        if let Some(scale) = track_value(&clip.scale, step) {
+            transform.scale = Vec3::new(base_scale.x * scale, base_scale.y * scale, base_scale.z);
+        }
+        if let Some(mut sprite) = sprite {
+            if let Some(alpha) = track_value(&clip.alpha, step) {
+                sprite.color.set_a(alpha);
+            }
+        }
+        if let Some(mut atlas_sprite) = atlas_sprite {
+            if let Some(index) = track_value(&clip.frames, step) {
+                atlas_sprite.index = index;
+            }
+            if let Some(alpha) = track_value(&clip.alpha, step) {
+                atlas_sprite.color.set_a(alpha);
+            }
+        }
+    }
+}
+
+fn track_value<T: Copy>(track: &[T], step: usize) -> Option<T> {
+    if track.is_empty() {
+        None
+    } else {
+        Some(track[step % track.len()])
+    }
+}
+
+fn despawn_on_finish_system(
+    mut commands: Commands,
+    mut ev_finished: EventReader<AnimationFinished>,
+    query: Query<(), With<DespawnOnFinish>>,
+) {
+    for ev in ev_finished.iter() {
+        if query.get(ev.entity).is_ok() {
+            commands.entity(ev.entity).despawn_recursive();
+        }
+    }
+}
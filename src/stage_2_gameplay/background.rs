@@ -0,0 +1,238 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use iyes_loopless::prelude::{ConditionHelpers, ConditionSet, IntoConditionalSystem};
+use rand::{thread_rng, Rng};
+use serde::Deserialize;
+
+use super::components::{Scenery, Star};
+use super::constants::{BACKGROUND_THEMES, SCENERY_INTERVAL};
+use super::resources::Wave;
+use crate::shared::components::GameRunning;
+use crate::shared::general::load_asset_data;
+use crate::shared::resources::{AppState, WinSize};
+
+pub struct BackgroundPlugin;
+
+impl Plugin for BackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            background_setup_system
+                .run_in_state(AppState::Gameplay)
+                .run_if_resource_added::<GameRunning>(),
+        )
+        // background only moves during gameplay so it stops when paused
+        .add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::Gameplay)
+                .run_if_resource_exists::<BackgroundAssets>()
+                .with_system(star_scroll_system)
+                .with_system(scenery_spawn_system)
+                .with_system(scenery_scroll_system)
+                .with_system(background_theme_system)
+                .into(),
+        );
+    }
+}
+
+// Colors of one of the backgrounds the waves cycle through
+#[derive(Clone, Debug, Deserialize)]
+pub struct BackgroundTheme {
+    pub clear_color: (f32, f32, f32),
+    pub star_color: (f32, f32, f32),
+    pub scenery_colors: Vec<(f32, f32, f32)>,
+    pub planet_color: (f32, f32, f32),
+}
+
+// Resource - themes and generated textures used by the background
+pub struct BackgroundAssets {
+    pub themes: Vec<BackgroundTheme>,
+    pub glow: Handle<Image>,
+    pub planet: Handle<Image>,
+    pub scenery_timer: Timer,
+    pub current_theme: Option<usize>,
+}
+
+impl BackgroundAssets {
+    // Every wave has its own theme, they repeat once all of them were used
+    pub fn theme_index(&self, wave: &Wave) -> usize {
+        (wave.number as usize - 1) % self.themes.len()
+    }
+
+    pub fn theme(&self, wave: &Wave) -> &BackgroundTheme {
+        &self.themes[self.theme_index(wave)]
+    }
+}
+
+// Star layers from the farthest to the closest: speed, size, brightness, count
+const STAR_LAYERS: [(f32, f32, f32, usize); 3] =
+    [(20., 1., 0.4, 60), (45., 2., 0.7, 35), (90., 3., 1., 15)];
+
+fn background_setup_system(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    win_size: Res<WinSize>,
+) {
+    let mut rng = thread_rng();
+
+    for (speed, size, brightness, count) in STAR_LAYERS {
+        for _ in 0..count {
+            let x = rng.gen_range(-win_size.w / 2.0..win_size.w / 2.);
+            let y = rng.gen_range(-win_size.h / 2.0..win_size.h / 2.);
+
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::splat(size)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(x, y, -10. + speed / 100.),
+                    ..Default::default()
+                })
+                .insert(Star { speed, brightness });
+        }
+    }
+
+    commands.insert_resource(BackgroundAssets {
+        themes: load_asset_data(BACKGROUND_THEMES),
+        glow: images.add(circle_image(64, 1.)),
+        planet: images.add(circle_image(64, 0.1)),
+        scenery_timer: Timer::from_seconds(SCENERY_INTERVAL, true),
+        current_theme: None,
+    });
+}
+
+// White circle texture, softness decides how much of the radius fades out
+fn circle_image(size: u32, softness: f32) -> Image {
+    let center = size as f32 / 2.;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+
+    for y in 0..size {
+        for x in 0..size {
+            let dx = x as f32 + 0.5 - center;
+            let dy = y as f32 + 0.5 - center;
+            let distance = (dx * dx + dy * dy).sqrt() / center;
+            let alpha = ((1. - distance) / softness).clamp(0., 1.);
+            data.extend_from_slice(&[255, 255, 255, (alpha * 255.) as u8]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn star_scroll_system(
+    time: Res<Time>,
+    win_size: Res<WinSize>,
+    mut query: Query<(&Star, &mut Transform)>,
+) {
+    let mut rng = thread_rng();
+
+    for (star, mut transform) in query.iter_mut() {
+        transform.translation.y -= star.speed * time.delta_seconds();
+
+        if transform.translation.y < -win_size.h / 2. - 5. {
+            transform.translation.y = win_size.h / 2. + 5.;
+            transform.translation.x = rng.gen_range(-win_size.w / 2.0..win_size.w / 2.);
+        }
+    }
+}
+
+fn scenery_spawn_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    win_size: Res<WinSize>,
+    wave: Res<Wave>,
+    mut assets: ResMut<BackgroundAssets>,
+) {
+    assets.scenery_timer.tick(time.delta());
+    if !assets.scenery_timer.just_finished() {
+        return;
+    }
+
+    let mut rng = thread_rng();
+    let theme = assets.theme(&wave);
+
+    // mostly nebulas, every now and then a planet
+    let (texture, color, size, speed, z) = if rng.gen_bool(0.25) {
+        let (r, g, b) = theme.planet_color;
+        let size = rng.gen_range(60. ..140.);
+        (assets.planet.clone(), Color::rgb(r, g, b), size, 30., -15.)
+    } else {
+        let (r, g, b) = theme.scenery_colors[rng.gen_range(0..theme.scenery_colors.len())];
+        let size = rng.gen_range(200. ..350.);
+        (
+            assets.glow.clone(),
+            Color::rgba(r, g, b, 0.35),
+            size,
+            15.,
+            -20.,
+        )
+    };
+
+    let x = rng.gen_range(-win_size.w / 2.0..win_size.w / 2.);
+    let y = win_size.h / 2. + size / 2.;
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture,
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::splat(size)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(x, y, z),
+            ..Default::default()
+        })
+        .insert(Scenery { speed });
+}
+
+fn scenery_scroll_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    win_size: Res<WinSize>,
+    mut query: Query<(Entity, &Scenery, &Sprite, &mut Transform)>,
+) {
+    for (entity, scenery, sprite, mut transform) in query.iter_mut() {
+        transform.translation.y -= scenery.speed * time.delta_seconds();
+
+        let half_size = sprite.custom_size.map_or(0., |size| size.y / 2.);
+        if transform.translation.y < -win_size.h / 2. - half_size {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn background_theme_system(
+    wave: Res<Wave>,
+    mut assets: ResMut<BackgroundAssets>,
+    mut clear_color: ResMut<ClearColor>,
+    mut query: Query<(&Star, &mut Sprite)>,
+) {
+    let index = assets.theme_index(&wave);
+    if assets.current_theme == Some(index) {
+        return;
+    }
+    assets.current_theme = Some(index);
+
+    let theme = &assets.themes[index];
+    let (r, g, b) = theme.clear_color;
+    clear_color.0 = Color::rgb(r, g, b);
+
+    // farther stars are dimmer
+    let (r, g, b) = theme.star_color;
+    for (star, mut sprite) in query.iter_mut() {
+        sprite.color = Color::rgb(
+            r * star.brightness,
+            g * star.brightness,
+            b * star.brightness,
+        );
+    }
+}
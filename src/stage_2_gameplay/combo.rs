@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::{ConditionSet, IntoConditionalSystem};
+
+use super::components::{Announcement, ComboMeter, ComboText, PlayerHit};
+use super::constants::{COMBO_STREAKS, HIT_PROCESSING};
+use super::enemy::components::EnemyKilled;
+use super::resources::{Combo, HitStop, PlayerState};
+use crate::shared::resources::{AppState, UiTextures};
+
+pub struct ComboPlugin;
+
+impl Plugin for ComboPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            score_system
+                .run_in_state(AppState::Gameplay)
+                .after(HIT_PROCESSING),
+        )
+        .add_system(
+            combo_system
+                .run_in_state(AppState::Gameplay)
+                .after(HIT_PROCESSING),
+        )
+        .add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::Gameplay)
+                .run_unless_resource_exists::<HitStop>()
+                .with_system(combo_timer_system)
+                .into(),
+        )
+        .add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::Gameplay)
+                .with_system(combo_meter_system)
+                .with_system(announcement_system)
+                .into(),
+        );
+    }
+}
+
+fn score_system(mut player_state: ResMut<PlayerState>, mut ev_killed: EventReader<EnemyKilled>) {
+    for kill in ev_killed.iter() {
+        player_state.add_score(kill.score);
+    }
+}
+
+fn combo_system(
+    mut commands: Commands,
+    ui_textures: Res<UiTextures>,
+    mut combo: ResMut<Combo>,
+    mut player_state: ResMut<PlayerState>,
+    mut ev_killed: EventReader<EnemyKilled>,
+    mut ev_hit: EventReader<PlayerHit>,
+) {
+    for kill in ev_killed.iter() {
+        // the kill itself is scored on its own, the multiplier adds the rest
+        player_state.add_score(kill.score * (combo.multiplier() - 1));
+
+        let multiplier = combo.multiplier();
+        combo.register_kill();
+
+        let streak = COMBO_STREAKS
+            .iter()
+            .find(|(kills, _)| *kills == combo.streak)
+            .map(|(_, name)| name.to_string());
+        let announcement = match streak {
+            Some(name) => Some(name),
+            None if combo.multiplier() > multiplier => {
+                Some(format!("Combo x{}", combo.multiplier()))
+            }
+            None => None,
+        };
+
+        if let Some(announcement) = announcement {
+            spawn_announcement(&mut commands, &ui_textures, announcement);
+        }
+    }
+
+    // getting hit ends the streak
+    if ev_hit.iter().count() > 0 {
+        combo.reset();
+    }
+}
+
+pub fn spawn_announcement(commands: &mut Commands, ui_textures: &UiTextures, value: String) {
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::with_section(
+                value,
+                TextStyle {
+                    font: ui_textures.ui_font.clone(),
+                    font_size: 48.0,
+                    color: Color::rgb(1., 0.8, 0.2),
+                },
+                TextAlignment {
+                    vertical: VerticalAlign::Center,
+                    horizontal: HorizontalAlign::Center,
+                },
+            ),
+            transform: Transform::from_xyz(0., 120., 30.),
+            ..Default::default()
+        })
+        .insert(Announcement::default());
+}
+
+fn combo_timer_system(time: Res<Time>, mut combo: ResMut<Combo>) {
+    combo.tick(time.delta());
+}
+
+fn combo_meter_system(
+    combo: Res<Combo>,
+    mut text_query: Query<&mut Text, With<ComboText>>,
+    mut meter_query: Query<&mut Style, With<ComboMeter>>,
+) {
+    if !combo.is_changed() {
+        return;
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = if combo.multiplier() > 1 {
+            format!("Combo x{}", combo.multiplier())
+        } else {
+            String::new()
+        };
+    }
+
+    for mut style in meter_query.iter_mut() {
+        style.size.width = Val::Percent(combo.remaining() * 100.);
+    }
+}
+
+fn announcement_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Announcement, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut announcement, mut transform, mut text) in query.iter_mut() {
+        announcement.0.tick(time.delta());
+
+        // grows a little while fading out
+        let t = announcement.0.percent();
+        transform.scale = Vec3::splat(1. + t * 0.3);
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(1. - t);
+        }
+
+        if announcement.0.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
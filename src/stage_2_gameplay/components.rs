@@ -1,12 +1,17 @@
 use bevy::{
     core::Timer,
     math::{Vec2, Vec3},
-    prelude::{Component, Entity},
+    prelude::{Color, Component, Entity},
+    utils::HashSet,
 };
+use serde::{Deserialize, Serialize};
+
+use super::constants::OFFSCREEN_MARGIN;
+use crate::shared::resources::AppState;
 
 // Common Components
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 pub struct Point {
     pub x: f32,
     pub y: f32,
@@ -18,14 +23,36 @@ pub struct Velocity {
     pub y: f32,
 }
 
+// Moved by its velocity every frame
 #[derive(Component)]
-pub struct Movable {
-    pub auto_despawn: bool,
+pub struct Movable;
+
+// What happens to the entity once it reaches the edge of the screen
+#[derive(Component, Clone, Copy, Debug)]
+pub enum OffscreenPolicy {
+    // despawned once it's further than the margin past the edge
+    Despawn(f32),
+    // comes back in on the opposite edge
+    Wrap,
+    // turns around at the edge
+    Bounce,
+    // can't leave the screen
+    Clamp,
+}
+
+impl Default for OffscreenPolicy {
+    fn default() -> Self {
+        OffscreenPolicy::Despawn(OFFSCREEN_MARGIN)
+    }
 }
 
 #[derive(Component)]
 pub struct Laser;
 
+// Damage dealt to whatever this entity hits
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Damage(pub f32);
+
 #[derive(Component)]
 pub struct SpriteSize(pub Vec2);
 
@@ -38,15 +65,11 @@ impl From<(f32, f32)> for SpriteSize {
 #[derive(Component)]
 pub struct Invincibility {
     pub length: f32,
-    pub animation_timer: Timer,
 }
 
 impl From<f32> for Invincibility {
     fn from(val: f32) -> Self {
-        Invincibility {
-            length: val,
-            animation_timer: Timer::from_seconds(0.2, true),
-        }
+        Invincibility { length: val }
     }
 }
 
@@ -59,7 +82,7 @@ impl Default for FiringCooldownTimer {
     }
 }
 
-#[derive(Component, Clone, Debug)]
+#[derive(Component, Clone, Debug, Deserialize)]
 pub enum EntityType {
     Player,
     Asteroid,
@@ -75,9 +98,6 @@ impl Default for EntityType {
 #[derive(Component)]
 pub struct IsHittable;
 
-#[derive(Component)]
-pub struct IsHit;
-
 #[derive(Component, Clone, Debug)]
 pub enum FromEntity {
     FromPlayer,
@@ -89,6 +109,81 @@ pub enum FromEntity {
 #[derive(Component)]
 pub struct Player;
 
+// Player got a captured ship back and flies with a second fighter next to it
+#[derive(Component)]
+pub struct DualFighter;
+
+// Weapon Components
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum WeaponKind {
+    TwinLaser,
+    SpreadShot,
+    PiercingBeam,
+    HomingMissile,
+    ChargeShot,
+}
+
+#[derive(Component)]
+pub struct Weapon {
+    pub kind: WeaponKind,
+    pub level: u8,
+    pub charge: f32, // seconds the fire button has been held for
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        Self {
+            kind: WeaponKind::TwinLaser,
+            level: 1,
+            charge: 0.,
+        }
+    }
+}
+
+// Projectile keeps flying after a hit, remembers who it has already hit
+#[derive(Component, Default)]
+pub struct Piercing(pub HashSet<Entity>);
+
+// Projectile steers towards the closest enemy
+#[derive(Component)]
+pub struct Homing {
+    pub turn_rate: f32, // radians per second
+}
+
+// How a projectile reacts to projectiles of the other side, by default they pass through each other
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ProjectileClash {
+    pub cancels: bool,      // destroys destructible projectiles it runs into
+    pub destructible: bool, // destroyed by projectiles that cancel
+}
+
+impl ProjectileClash {
+    pub const CANCELS: Self = ProjectileClash {
+        cancels: true,
+        destructible: true,
+    };
+    pub const INDESTRUCTIBLE: Self = ProjectileClash {
+        cancels: true,
+        destructible: false,
+    };
+}
+
+// Pickup Components
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum PickupKind {
+    Heal,
+    WeaponUpgrade,
+}
+
+#[derive(Component)]
+pub struct Pickup(pub PickupKind);
+
+#[derive(Component)]
+pub struct PickupToSpawn(pub PickupKind, pub Vec3);
+
 // Explosion Components
 
 #[derive(Component)]
@@ -97,23 +192,218 @@ pub struct Explosion;
 #[derive(Component)]
 pub struct ExplosionToSpawn(pub Vec3);
 
+// Damage Number Components
+
 #[derive(Component)]
-pub struct ExplosionTimer(pub Timer);
+pub struct DamageNumber(pub Timer);
 
-impl Default for ExplosionTimer {
+impl Default for DamageNumber {
     fn default() -> Self {
-        Self(Timer::from_seconds(0.05, true))
+        Self(Timer::from_seconds(0.8, false))
+    }
+}
+
+#[derive(Component)]
+pub struct DamageNumberToSpawn(pub f32, pub Vec3);
+
+// Hit Feedback Components
+
+// Sprite is tinted white until the timer runs out, then gets its color back
+#[derive(Component)]
+pub struct HitFlash {
+    pub timer: Timer,
+    pub color: Color,
+}
+
+impl HitFlash {
+    pub fn new(color: Color) -> Self {
+        Self {
+            timer: Timer::from_seconds(0.1, false),
+            color,
+        }
+    }
+}
+
+// Pushes the entity away, slows down until it stops
+#[derive(Component)]
+pub struct Knockback(pub Vec2);
+
+// Particle Components
+
+// Describes how the particles of an emitter or burst look and behave
+#[derive(Clone)]
+pub struct ParticleConfig {
+    pub lifetime: f32,
+    pub direction: f32, // radians, 0 is up
+    pub spread: f32,    // max deviation from the direction in radians
+    pub speed: (f32, f32),
+    pub area: Vec2, // particles spawn randomly inside this box
+    pub size: (f32, f32),
+    pub color: (Color, Color),
+    pub z: f32,
+}
+
+// Keeps spawning particles at the entity's position
+#[derive(Component)]
+pub struct ParticleEmitter {
+    pub config: ParticleConfig,
+    pub rate: f32, // particles per second
+    pub offset: Vec2,
+    pub pending: f32,
+}
+
+impl ParticleEmitter {
+    pub fn new(config: ParticleConfig, rate: f32, offset: Vec2) -> Self {
+        Self {
+            config,
+            rate,
+            offset,
+            pending: 0.,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Particle {
+    pub velocity: Vec2,
+    pub lifetime: Timer,
+    pub size: (f32, f32),
+    pub color: (Color, Color),
+}
+
+// One-shot burst of particles
+#[derive(Component)]
+pub struct ParticlesToSpawn {
+    pub config: ParticleConfig,
+    pub count: usize,
+    pub translation: Vec3,
+}
+
+// Background Components
+
+// Wraps back to the top once it scrolls past the bottom
+#[derive(Component)]
+pub struct Star {
+    pub speed: f32,
+    pub brightness: f32,
+}
+
+// Nebulas and planets, despawned once they scroll past the bottom
+#[derive(Component)]
+pub struct Scenery {
+    pub speed: f32,
+}
+
+// Animation Components
+
+// Plays a clip from the animation library
+#[derive(Component)]
+pub struct Animation {
+    pub clip: String,
+    pub step: usize,
+    pub timer: Option<Timer>, // created from the clip on the first update
+    pub base_scale: Option<Vec3>,
+    pub finished: bool,
+}
+
+impl Animation {
+    pub fn new(clip: &str) -> Self {
+        Self {
+            clip: clip.to_string(),
+            step: 0,
+            timer: None,
+            base_scale: None,
+            finished: false,
+        }
     }
 }
 
+// Entity is despawned once its one-shot animation is over
+#[derive(Component)]
+pub struct DespawnOnFinish;
+
 // Ui Components
 
 #[derive(Component)]
 pub struct HeartImage;
 
+#[derive(Component)]
+pub struct ScoreText;
+
+#[derive(Component)]
+pub struct WeaponText;
+
+#[derive(Component)]
+pub struct LivesText;
+
+#[derive(Component)]
+pub struct ComboText;
+
+// Bar that empties as the combo runs out of time
+#[derive(Component)]
+pub struct ComboMeter;
+
+// Big text in the middle of the screen that fades out, ie. kill streaks
+#[derive(Component)]
+pub struct Announcement(pub Timer);
+
+impl Default for Announcement {
+    fn default() -> Self {
+        Self(Timer::from_seconds(1.5, false))
+    }
+}
+
+#[derive(Component)]
+pub struct DebugOverlay;
+
+// Hitbox outlines and formation points drawn while debugging, redrawn every frame
+#[derive(Component)]
+pub struct DebugMarker;
+
+// Drop-down panel of the developer console
+#[derive(Component)]
+pub struct ConsolePanel;
+
+#[derive(Component)]
+pub struct ConsoleText;
+
 // Events
 
+pub struct AnimationFinished {
+    pub entity: Entity,
+    pub clip: String,
+}
+
+// Every gameplay entity is despawned through this request, cleanup happens in one place
 pub struct DespawnEntity {
     pub entity: Entity,
-    pub entity_type: EntityType,
+}
+
+// Two things ran into each other, sent by the collision detection systems
+pub struct CollisionEvent {
+    pub entities: [Entity; 2],
+    pub contact: Vec3,
+}
+
+// Target takes damage from the source, a target can be hit several times in the same frame
+pub struct DamageEvent {
+    pub source: Entity,
+    pub target: Entity,
+    pub contact: Vec3,
+    pub damage: f32,
+}
+
+pub struct PlayerHit {
+    pub damage: f32,
+}
+
+// Command typed into the developer console
+pub enum ConsoleCommand {
+    Spawn(String, u32),
+    God,
+    Heal,
+    Wave(u32),
+    TimeScale(f32),
+    State(AppState),
+    Seed(u64),
 }
@@ -0,0 +1,265 @@
+use std::str::FromStr;
+
+use bevy::prelude::*;
+use iyes_loopless::prelude::{
+    AppLooplessStateExt, ConditionHelpers, ConditionSet, IntoConditionalSystem,
+};
+use iyes_loopless::state::NextState;
+
+use super::components::{ConsoleCommand, ConsolePanel, ConsoleText, Player, Velocity};
+use super::constants::CONSOLE_LOG_LINES;
+use super::enemy::archetype::EnemyRegistry;
+use super::enemy::components::{EnemyCount, EnemySpawnEvent, SpawnEnemy};
+use super::resources::{GameRng, GodMode, PlayerState, TimeScale, Wave};
+use crate::shared::components::{GameRunning, GameplayTeardown};
+use crate::shared::general::grave_pressed;
+use crate::shared::resources::{AppState, UiTextures};
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ConsoleCommand>()
+            // backtick opens and closes the console
+            .add_system(
+                console_toggle_system
+                    .run_in_state(AppState::Gameplay)
+                    .run_if(grave_pressed),
+            )
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(AppState::Gameplay)
+                    .run_if_resource_exists::<Console>()
+                    .run_if_resource_exists::<EnemyRegistry>()
+                    .with_system(console_input_system)
+                    .with_system(console_command_system)
+                    .with_system(console_text_system)
+                    .into(),
+            )
+            .add_exit_system(AppState::Gameplay, console_close_system)
+            .add_system(console_cleanup_system.run_if_resource_removed::<Console>());
+    }
+}
+
+// Resource - exists while the console is open, player controls are ignored meanwhile
+#[derive(Default)]
+pub struct Console {
+    pub input: String,
+    pub log: Vec<String>,
+}
+
+impl Console {
+    fn print(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        if self.log.len() > CONSOLE_LOG_LINES {
+            self.log.remove(0);
+        }
+    }
+}
+
+fn console_toggle_system(
+    mut commands: Commands,
+    ui_textures: Res<UiTextures>,
+    console: Option<Res<Console>>,
+    mut query: Query<&mut Velocity, With<Player>>,
+) {
+    if console.is_some() {
+        commands.remove_resource::<Console>();
+        return;
+    }
+
+    // player would otherwise keep drifting in the direction it was moving
+    if let Ok(mut velocity) = query.get_single_mut() {
+        velocity.x = 0.;
+    }
+
+    commands.insert_resource(Console::default());
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(0.),
+                    top: Val::Px(0.),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Percent(100.), Val::Auto),
+                padding: Rect::all(Val::Px(8.)),
+                ..Default::default()
+            },
+            color: Color::rgba(0., 0., 0., 0.8).into(),
+            ..Default::default()
+        })
+        .insert(ConsolePanel)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            font: ui_textures.ui_font.clone(),
+                            font_size: 18.0,
+                            color: Color::rgb(0.9, 0.9, 0.9),
+                        },
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                })
+                .insert(ConsoleText);
+        });
+}
+
+fn console_input_system(
+    kb: Res<Input<KeyCode>>,
+    mut console: ResMut<Console>,
+    mut ev_char: EventReader<ReceivedCharacter>,
+    mut ev_command: EventWriter<ConsoleCommand>,
+) {
+    for ev in ev_char.iter() {
+        if !ev.char.is_control() && ev.char != '`' {
+            console.input.push(ev.char);
+        }
+    }
+
+    if kb.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+
+    if kb.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        if line.trim().is_empty() {
+            return;
+        }
+
+        console.print(format!("> {}", line));
+        match parse_command(&line) {
+            Ok(command) => ev_command.send(command),
+            Err(error) => console.print(error),
+        }
+    }
+}
+
+fn parse_command(line: &str) -> Result<ConsoleCommand, String> {
+    let args: Vec<&str> = line.split_whitespace().collect();
+
+    match args.as_slice() {
+        ["spawn", archetype] => Ok(ConsoleCommand::Spawn(archetype.to_string(), 1)),
+        ["spawn", archetype, count] => Ok(ConsoleCommand::Spawn(
+            archetype.to_string(),
+            parse_arg(count)?,
+        )),
+        ["god"] => Ok(ConsoleCommand::God),
+        ["heal"] => Ok(ConsoleCommand::Heal),
+        ["wave", number] => Ok(ConsoleCommand::Wave(parse_arg(number)?)),
+        ["timescale", scale] => Ok(ConsoleCommand::TimeScale(parse_arg(scale)?)),
+        ["state", state] => {
+            let state = match *state {
+                "mainmenu" => AppState::MainMenu,
+                "gameplay" => AppState::Gameplay,
+                "paused" => AppState::Paused,
+                "gameover" => AppState::GameOver,
+                _ => return Err(format!("Unknown state: {}", state)),
+            };
+            Ok(ConsoleCommand::State(state))
+        }
+        ["seed", seed] => Ok(ConsoleCommand::Seed(parse_arg(seed)?)),
+        _ => Err(format!("Unknown command: {}", line.trim())),
+    }
+}
+
+fn parse_arg<T: FromStr>(arg: &str) -> Result<T, String> {
+    arg.parse()
+        .map_err(|_| format!("Invalid argument: {}", arg))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn console_command_system(
+    mut commands: Commands,
+    mut console: ResMut<Console>,
+    mut player_state: ResMut<PlayerState>,
+    mut wave: ResMut<Wave>,
+    enemy_count: Res<EnemyCount>,
+    registry: Res<EnemyRegistry>,
+    god_mode: Option<Res<GodMode>>,
+    mut ev_command: EventReader<ConsoleCommand>,
+    mut ev_spawn: EventWriter<EnemySpawnEvent>,
+) {
+    for command in ev_command.iter() {
+        match command {
+            ConsoleCommand::Spawn(archetype, count) => {
+                if registry.get(archetype).is_none() {
+                    console.print(format!("Unknown enemy: {}", archetype));
+                    continue;
+                }
+
+                for _ in 0..*count {
+                    ev_spawn.send(EnemySpawnEvent(SpawnEnemy {
+                        archetype: archetype.clone(),
+                        starting_point: None,
+                    }));
+                }
+                console.print(format!(
+                    "Spawning {} {} ({} asteroids, {} minions on screen)",
+                    count, archetype, enemy_count.asteroids, enemy_count.minions
+                ));
+            }
+            ConsoleCommand::God => {
+                if god_mode.is_some() {
+                    commands.remove_resource::<GodMode>();
+                    console.print("God mode off");
+                } else {
+                    commands.insert_resource(GodMode);
+                    console.print("God mode on");
+                }
+            }
+            ConsoleCommand::Heal => {
+                player_state.respawn();
+                console.print("Healed");
+            }
+            ConsoleCommand::Wave(number) => {
+                wave.number = (*number).max(1);
+                wave.kills = 0;
+                console.print(format!("Wave {}", wave.number));
+            }
+            ConsoleCommand::TimeScale(scale) => {
+                let scale = scale.max(0.);
+                commands.insert_resource(TimeScale(scale));
+                console.print(format!("Time scale {}", scale));
+            }
+            ConsoleCommand::State(AppState::MainMenu) => {
+                // same way out as the exit button of the pause menu
+                commands.remove_resource::<GameRunning>();
+                commands.insert_resource(GameplayTeardown);
+            }
+            ConsoleCommand::State(state) => {
+                commands.insert_resource(NextState(state.clone()));
+            }
+            ConsoleCommand::Seed(seed) => {
+                commands.insert_resource(GameRng::from_seed(*seed));
+                console.print(format!("Seed {}", seed));
+            }
+        }
+    }
+}
+
+fn console_text_system(console: Res<Console>, mut query: Query<&mut Text, With<ConsoleText>>) {
+    if !console.is_changed() {
+        return;
+    }
+
+    if let Ok(mut text) = query.get_single_mut() {
+        let mut lines = console.log.clone();
+        lines.push(format!("> {}_", console.input));
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+fn console_close_system(mut commands: Commands) {
+    commands.remove_resource::<Console>();
+}
+
+fn console_cleanup_system(mut commands: Commands, query: Query<Entity, With<ConsolePanel>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,3 +1,5 @@
+use bevy::prelude::*;
+
 // Asset Constants
 
 pub const PLAYER_SPRITE: &str = "player_a_01.png";
@@ -5,28 +7,107 @@ pub const PLAYER_SIZE: (f32, f32) = (144., 75.);
 pub const PLAYER_LASER_SPRITE: &str = "laser_a_01.png";
 pub const PLAYER_LASER_SIZE: (f32, f32) = (9., 54.);
 
-pub const ENEMY_SPRITE: &str = "enemy_a_01.png";
-pub const ENEMY_SIZE: (f32, f32) = (144., 75.);
 pub const ENEMY_LASER_SPRITE: &str = "laser_b_01.png";
 pub const ENEMY_LASER_SIZE: (f32, f32) = (17., 55.);
 
 pub const EXPLOSION_SHEET: &str = "explo_a_sheet.png";
-pub const EXPLOSION_LEN: usize = 16;
+
+pub const PICKUP_SIZE: (f32, f32) = (53., 45.);
+pub const WEAPON_UPGRADE_SPRITE: &str = "player_b_01.png";
+pub const WEAPON_UPGRADE_SIZE: (f32, f32) = (144., 75.);
 
 pub const SPRITE_SCALE: f32 = 0.5;
 
+// Data Constants
+
+pub const ENEMY_ARCHETYPES: &str = "enemies.ron";
+pub const BACKGROUND_THEMES: &str = "backgrounds.ron";
+pub const ANIMATION_CLIPS: &str = "animations.ron";
+pub const ENEMY_PATHS: &str = "paths.ron";
+pub const COLLISION_RULES: &str = "collisions.ron";
+pub const EXPLOSION_ANIMATION: &str = "explosion";
+pub const INVINCIBILITY_ANIMATION: &str = "invincibility";
+pub const PICKUP_ANIMATION: &str = "pickup";
+pub const THRUSTER_ANIMATION: &str = "thruster";
+pub const ASTEROID_ARCHETYPE: &str = "asteroid";
+pub const MINION_ARCHETYPE: &str = "minion";
+pub const CAPTOR_ARCHETYPE: &str = "captor";
+pub const SWOOPER_ARCHETYPE: &str = "swooper";
+pub const LOOPER_ARCHETYPE: &str = "looper";
+pub const BOSS_ARCHETYPE: &str = "mothership";
+
 // Game Constants
 
 pub const TIME_STEP: f32 = 1. / 60.;
 pub const BASE_SPEED: f32 = 500.;
 
-pub const PLAYER_RESPAWN_DELAY: f64 = 2.;
+pub const PLAYER_MAX_HEALTH: usize = 3;
+pub const PLAYER_INVINCIBILITY: f32 = 3.; // seconds of invincibility after spawning or a hit
+pub const PLAYER_RESPAWN_DELAY: f32 = 2.;
+pub const PLAYER_LIVES: u32 = 3;
+pub const PLAYER_MAX_LIVES: u32 = 5;
+pub const MAX_CONTINUES: u32 = 3;
+pub const EXTRA_LIFE_SCORE: u32 = 3000; // an extra life every time the score grows by this much
+pub const PLAYER_COLLISION_DAMAGE: f32 = 1.;
+pub const POINTER_MAX_SPEED: f32 = 1.; // fastest the ship follows the mouse, same as the keyboard
 pub const ENEMY_MAX: u32 = 2;
 pub const FORMATION_MEMBERS_MAX: u32 = 2;
+pub const PATH_SEGMENT_STEPS: usize = 16; // straight pieces every curve segment is split into
+
+// Dive attacks
+pub const DIVE_CHANCE: f64 = 0.006; // chance per frame that a minion in its slot starts a dive
+pub const DIVE_MAX: usize = 2; // enemies diving at the same time
+pub const DIVE_SPEED: [f32; 4] = [0.6, 1.2, 1.2, 0.7]; // speed curve along the dive
+pub const DIVE_FIRE_MULTIPLIER: f64 = 4.; // divers fire this much more often
+pub const TRACTOR_BEAM_TIME: f32 = 2.5;
+pub const TRACTOR_BEAM_SIZE: (f32, f32) = (70., 200.);
+pub const CAPTOR_WAVE: u32 = 3; // first wave captors show up in
+pub const CAPTOR_CHANCE: f64 = 0.2; // chance a minion spawn is a captor instead
+
+pub const WEAPON_MAX_LEVEL: u8 = 3;
+
+// Combo
+pub const COMBO_TIME: f32 = 2.5; // seconds until the next kill has to happen
+pub const COMBO_KILLS_PER_LEVEL: u32 = 3; // kills needed to raise the multiplier
+pub const COMBO_MAX_MULTIPLIER: u32 = 5;
+pub const COMBO_STREAKS: [(u32, &str); 3] =
+    [(10, "Killing spree"), (20, "Rampage"), (35, "Unstoppable")];
+
+// Adaptive difficulty
+pub const DIRECTOR_MIN_TENSION: f32 = 0.5;
+pub const DIRECTOR_MAX_TENSION: f32 = 1.5;
+pub const DIRECTOR_WINDOW: f32 = 30.; // seconds of play the director looks back at
+pub const DIRECTOR_RESPONSE: f32 = 0.5; // how fast tension follows the players performance
+pub const DIRECTOR_KILL_PACE: f32 = 20.; // kills per minute of a player doing fine
+pub const DIRECTOR_HIT_PACE: f32 = 4.; // hits per minute of a struggling player
+pub const CHARGE_THRESHOLD: f32 = 0.35; // seconds fire has to be held before the weapon charges
+pub const CHARGE_TIME: f32 = 1.5; // seconds until a charge shot is fully charged
+
+// Tint of a sprite that was just hit, channels over 1 push every texel past full brightness and
+// the sprite renders white once the output is clamped
+pub const HIT_FLASH_COLOR: Color = Color::rgb(8., 8., 8.);
+pub const KNOCKBACK_SPEED: f32 = 150.;
+pub const HIT_STOP_TIME: f32 = 0.15;
+pub const SHAKE_PLAYER_HIT: f32 = 0.4;
+pub const SHAKE_BIG_EXPLOSION: f32 = 0.8;
+pub const SHAKE_MAX_OFFSET: f32 = 12.;
+
+pub const WAVE_KILLS: u32 = 15; // kills needed to advance to the next wave
+pub const SWOOPER_WAVE: u32 = 2; // first wave swoopers show up in
+pub const SWOOPER_CHANCE: f64 = 0.3; // chance a minion spawn is a swooper instead
+pub const LOOPER_WAVE: u32 = 4;
+pub const LOOPER_CHANCE: f64 = 0.25;
+pub const BOSS_WAVES: u32 = 5; // every this many waves start with a boss
+pub const SCENERY_INTERVAL: f32 = 12.; // seconds between nebulas and planets
+pub const OFFSCREEN_MARGIN: f32 = 200.; // distance past the edge before entities are despawned
+pub const CONSOLE_LOG_LINES: usize = 8; // lines of output the console keeps
 
 // Labels
 
 pub const GAMEPLAY_RESET: &str = "gameplay_reset";
 pub const HIT_DETECTION: &str = "hit_detection";
+pub const HIT_DAMAGE: &str = "hit_damage";
 pub const HIT_PROCESSING: &str = "hit_processing";
 pub const ENEMY_SPAWN: &str = "enemy_spawn";
+pub const MOVEMENT: &str = "movement";
+pub const PLAYER_INPUT: &str = "player_input";
@@ -0,0 +1,285 @@
+use bevy::diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin};
+use bevy::{math::Vec3Swizzles, prelude::*};
+use iyes_loopless::prelude::{ConditionSet, CurrentState, IntoConditionalSystem};
+
+use super::components::{DebugMarker, DebugOverlay, EntityType, SpriteSize};
+use super::enemy::archetype::EnemyRegistry;
+use super::enemy::components::{
+    Enemy, EnemyCount, EnemyMovement, EnemyMovementState, EnemySpawnEvent, SpawnEnemy,
+};
+use super::resources::Director;
+use crate::shared::general::f3_pressed;
+use crate::shared::resources::{AppState, Settings, UiTextures};
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(FrameTimeDiagnosticsPlugin)
+            // F3 turns debug mode on and off
+            .add_system(debug_toggle_system.run_if(f3_pressed))
+            .add_system_set(
+                ConditionSet::new()
+                    .run_if_resource_exists::<DebugMode>()
+                    .with_system(debug_overlay_spawn_system)
+                    .with_system(debug_overlay_system)
+                    .into(),
+            )
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(AppState::Gameplay)
+                    .run_if_resource_exists::<DebugMode>()
+                    .with_system(debug_marker_system)
+                    .with_system(debug_spawn_system)
+                    .into(),
+            )
+            .add_system(debug_cleanup_system.run_if_resource_removed::<DebugMode>());
+    }
+}
+
+// Resource - exists while debug mode is on
+//
+// F5 picks the enemy archetype to spawn, F6 spawns it
+#[derive(Default)]
+pub struct DebugMode {
+    pub spawn_index: usize,
+}
+
+fn debug_toggle_system(mut commands: Commands, debug_mode: Option<Res<DebugMode>>) {
+    if debug_mode.is_some() {
+        commands.remove_resource::<DebugMode>();
+    } else {
+        commands.insert_resource(DebugMode::default());
+    }
+}
+
+// Overlay also comes back after the gameplay teardown removed it
+fn debug_overlay_spawn_system(
+    mut commands: Commands,
+    ui_textures: Res<UiTextures>,
+    query: Query<(), With<DebugOverlay>>,
+) {
+    if !query.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(10.),
+                    bottom: Val::Px(10.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                TextStyle {
+                    font: ui_textures.ui_font.clone(),
+                    font_size: 20.0,
+                    color: Color::rgb(0.4, 1., 0.4),
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(DebugOverlay);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn debug_overlay_system(
+    diagnostics: Res<Diagnostics>,
+    state: Res<CurrentState<AppState>>,
+    settings: Res<Settings>,
+    debug_mode: Res<DebugMode>,
+    director: Option<Res<Director>>,
+    enemy_count: Option<Res<EnemyCount>>,
+    registry: Option<Res<EnemyRegistry>>,
+    entity_query: Query<(&EntityType, Option<&Enemy>)>,
+    mut query: Query<&mut Text, With<DebugOverlay>>,
+) {
+    let mut text = match query.get_single_mut() {
+        Ok(text) => text,
+        Err(_) => return,
+    };
+
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.average())
+        .unwrap_or(0.);
+
+    let mut lines = vec![format!("FPS {:.0}", fps), format!("State {:?}", state.0)];
+
+    // entity count per type, enemies are also checked against the EnemyCount bookkeeping
+    let (mut players, mut asteroids, mut minions) = (0, 0, 0);
+    let (mut enemy_asteroids, mut enemy_minions) = (0, 0);
+    for (entity_type, enemy) in entity_query.iter() {
+        match entity_type {
+            EntityType::Player => players += 1,
+            EntityType::Asteroid => asteroids += 1,
+            EntityType::Minion => minions += 1,
+        }
+        match (entity_type, enemy) {
+            (EntityType::Asteroid, Some(_)) => enemy_asteroids += 1,
+            (EntityType::Minion, Some(_)) => enemy_minions += 1,
+            _ => {}
+        }
+    }
+    lines.push(format!(
+        "Player {} Asteroid {} Minion {}",
+        players, asteroids, minions
+    ));
+    if let Some(enemy_count) = enemy_count {
+        lines.push(format!(
+            "EnemyCount {}/{} real {}/{}",
+            enemy_count.asteroids, enemy_count.minions, enemy_asteroids, enemy_minions
+        ));
+    }
+
+    if let Some(director) = director {
+        let adaptive = if settings.adaptive_difficulty {
+            "on"
+        } else {
+            "off"
+        };
+        lines.push(format!(
+            "Tension {:.2} (adaptive {})",
+            director.tension, adaptive
+        ));
+        lines.push(format!(
+            "Hits/min {:.1} Kills/min {:.1}",
+            director.hits_per_minute(),
+            director.kills_per_minute()
+        ));
+    }
+
+    if let Some(registry) = registry {
+        let names = registry.names();
+        if !names.is_empty() {
+            let name = names[debug_mode.spawn_index % names.len()];
+            lines.push(format!("Spawn {} (F5 pick, F6 spawn)", name));
+        }
+    }
+
+    text.sections[0].value = lines.join("\n");
+}
+
+fn debug_marker_system(
+    mut commands: Commands,
+    marker_query: Query<Entity, With<DebugMarker>>,
+    hitbox_query: Query<(&Transform, &SpriteSize)>,
+    formation_query: Query<&EnemyMovement>,
+) {
+    for entity in marker_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    // outline of what the collision systems use as the hitbox
+    for (tf, size) in hitbox_query.iter() {
+        let size = size.0 * tf.scale.xy();
+        let (x, y) = (tf.translation.x, tf.translation.y);
+        let (half_w, half_h) = (size.x / 2., size.y / 2.);
+        let color = Color::rgb(0.2, 1., 0.2);
+
+        spawn_marker(
+            &mut commands,
+            Vec2::new(x, y + half_h),
+            Vec2::new(size.x, 1.),
+            color,
+        );
+        spawn_marker(
+            &mut commands,
+            Vec2::new(x, y - half_h),
+            Vec2::new(size.x, 1.),
+            color,
+        );
+        spawn_marker(
+            &mut commands,
+            Vec2::new(x - half_w, y),
+            Vec2::new(1., size.y),
+            color,
+        );
+        spawn_marker(
+            &mut commands,
+            Vec2::new(x + half_w, y),
+            Vec2::new(1., size.y),
+            color,
+        );
+    }
+
+    // pivot of every circle formation and the point its members are heading to
+    for movement in formation_query.iter() {
+        if let EnemyMovementState::CircleFormation(formation) = &movement.state {
+            let pivot = Vec2::new(formation.pivot.x, formation.pivot.y);
+            let (x_radius, y_radius) = formation.radius;
+            let target = pivot
+                + Vec2::new(
+                    x_radius * movement.angle.cos(),
+                    y_radius * movement.angle.sin(),
+                );
+
+            spawn_marker(
+                &mut commands,
+                pivot,
+                Vec2::splat(6.),
+                Color::rgb(1., 0.3, 0.3),
+            );
+            spawn_marker(
+                &mut commands,
+                target,
+                Vec2::splat(6.),
+                Color::rgb(0.3, 0.6, 1.),
+            );
+        }
+    }
+}
+
+fn spawn_marker(commands: &mut Commands, position: Vec2, size: Vec2, color: Color) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(size),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(position.x, position.y, 50.),
+            ..Default::default()
+        })
+        .insert(DebugMarker);
+}
+
+fn debug_spawn_system(
+    kb: Res<Input<KeyCode>>,
+    registry: Option<Res<EnemyRegistry>>,
+    mut debug_mode: ResMut<DebugMode>,
+    mut ev_spawn: EventWriter<EnemySpawnEvent>,
+) {
+    if kb.just_pressed(KeyCode::F5) {
+        debug_mode.spawn_index += 1;
+    }
+
+    if kb.just_pressed(KeyCode::F6) {
+        if let Some(registry) = registry {
+            let names = registry.names();
+            if !names.is_empty() {
+                let name = names[debug_mode.spawn_index % names.len()];
+                ev_spawn.send(EnemySpawnEvent(SpawnEnemy {
+                    archetype: name.to_string(),
+                    starting_point: None,
+                }));
+            }
+        }
+    }
+}
+
+fn debug_cleanup_system(
+    mut commands: Commands,
+    overlay_query: Query<Entity, With<DebugOverlay>>,
+    marker_query: Query<Entity, With<DebugMarker>>,
+) {
+    for entity in overlay_query.iter().chain(marker_query.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::ConditionSet;
+
+use super::components::PlayerHit;
+use super::enemy::components::EnemyKilled;
+use super::resources::{Director, PlayerState};
+use crate::shared::resources::{AppState, Settings};
+
+pub struct DirectorPlugin;
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::Gameplay)
+                .with_system(director_system)
+                .into(),
+        );
+    }
+}
+
+fn director_system(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    player_state: Res<PlayerState>,
+    mut director: ResMut<Director>,
+    mut ev_killed: EventReader<EnemyKilled>,
+    mut ev_hit: EventReader<PlayerHit>,
+) {
+    for _ in ev_killed.iter() {
+        director.register_kill();
+    }
+    for _ in ev_hit.iter() {
+        director.register_hit();
+    }
+
+    let hearts = player_state.health as f32 / player_state.max_health.max(1) as f32;
+    director.update(time.delta_seconds(), hearts);
+
+    // performance is still tracked so turning it on mid run picks up right away
+    if !settings.adaptive_difficulty {
+        director.tension = 1.;
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::shared::general::load_asset_data;
+use crate::stage_2_gameplay::components::EntityType;
+use crate::stage_2_gameplay::constants::ENEMY_ARCHETYPES;
+
+use super::components::{DropEntry, EnemyMovementState, EnemyWeapon, SpawningDirection};
+
+// How one kind of enemy looks, moves, fights and what it's worth
+#[derive(Clone, Debug, Deserialize)]
+pub struct EnemyArchetype {
+    pub entity_type: EntityType,
+    pub sprite: String,
+    pub size: (f32, f32),
+    pub health: f32,
+    pub speed: f32,
+    pub collision_damage: f32, // dealt when ramming into something
+    pub spawn_direction: SpawningDirection,
+    pub movement: EnemyMovementState,
+    #[serde(default)]
+    pub weapon: Option<EnemyWeapon>,
+    #[serde(default)]
+    pub score: u32,
+    #[serde(default)]
+    pub drops: Vec<DropEntry>,
+    #[serde(default)]
+    pub boss: bool,
+    #[serde(default)]
+    pub tractor: bool, // can capture the player's ship
+    #[serde(default)]
+    pub animation: Option<String>, // idle animation clip
+}
+
+pub struct RegisteredEnemy {
+    pub archetype: EnemyArchetype,
+    pub texture: Handle<Image>,
+}
+
+// Resource - every known enemy archetype with its loaded texture
+#[derive(Default)]
+pub struct EnemyRegistry(HashMap<String, RegisteredEnemy>);
+
+impl EnemyRegistry {
+    pub fn load(asset_server: &AssetServer) -> Self {
+        let archetypes: HashMap<String, EnemyArchetype> = load_asset_data(ENEMY_ARCHETYPES);
+
+        let registry = archetypes
+            .into_iter()
+            .map(|(name, archetype)| {
+                let texture = asset_server.load(archetype.sprite.as_str());
+                (name, RegisteredEnemy { archetype, texture })
+            })
+            .collect();
+
+        EnemyRegistry(registry)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RegisteredEnemy> {
+        self.0.get(name)
+    }
+
+    // Archetype names in a stable order
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.0.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
@@ -1,10 +1,23 @@
+use std::sync::Arc;
+
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::stage_2_gameplay::components::{EntityType, PickupKind, Point, ProjectileClash};
+use crate::stage_2_gameplay::constants::DIVE_SPEED;
 
-use crate::stage_2_gameplay::components::{EntityType, Point};
+use super::path::{EnemyPath, PathCurve};
+use super::pattern::{BulletPattern, ShotStats};
 
 #[derive(Component)]
 pub struct Enemy;
 
+// Name of the archetype the enemy was spawned from
+#[derive(Component, Clone, Debug)]
+pub struct EnemyKind(pub String);
+
+// Enemies of each type on the board, kept up to date by the despawn pipeline
+#[derive(Debug, PartialEq)]
 pub struct EnemyCount {
     pub asteroids: u32,
     pub minions: u32,
@@ -19,6 +32,29 @@ impl Default for EnemyCount {
     }
 }
 
+impl EnemyCount {
+    fn of_type(&mut self, entity_type: &EntityType) -> Option<&mut u32> {
+        match entity_type {
+            EntityType::Asteroid => Some(&mut self.asteroids),
+            EntityType::Minion => Some(&mut self.minions),
+            EntityType::Player => None,
+        }
+    }
+
+    pub fn add(&mut self, entity_type: &EntityType) {
+        if let Some(count) = self.of_type(entity_type) {
+            *count += 1;
+        }
+    }
+
+    pub fn remove(&mut self, entity_type: &EntityType) {
+        if let Some(count) = self.of_type(entity_type) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
 pub enum SpawningDirection {
     Top,
     Sides,
@@ -37,10 +73,15 @@ pub struct EnemyBundle {
 
 pub struct EnemySpawnEvent(pub SpawnEnemy);
 
+pub struct EnemyKilled {
+    pub entity: Entity,
+    pub translation: Vec3,
+    pub score: u32,
+}
+
 pub struct SpawnEnemy {
-    pub bundle: EnemyBundle,
-    pub texture: Handle<Image>,
-    pub starting_point: Point,
+    pub archetype: String,
+    pub starting_point: Option<Point>, // archetypes spawning direction is used if not given
 }
 
 // Type components
@@ -51,15 +92,22 @@ pub struct Asteroid;
 #[derive(Component)]
 pub struct Minion;
 
+// Killing a boss freezes the game for a moment
+#[derive(Component)]
+pub struct Boss;
+
 // Motion components
 
-#[derive(Component, Clone, Debug)]
+#[derive(Component, Clone, Debug, Deserialize, Serialize)]
 pub enum EnemyMovementState {
     Stationary,
     Downward,
     Travel(Point),
     Seeking,
     CircleFormation(Formation),
+    FollowPath(PathFollow),
+    Dive(DiveAttack),
+    Tractor(TractorAttack),
 }
 
 impl Default for EnemyMovementState {
@@ -68,29 +116,148 @@ impl Default for EnemyMovementState {
     }
 }
 
-#[derive(Component, Clone, Debug, Default)]
+#[derive(Component, Clone, Debug, Default, Deserialize, Serialize)]
 pub struct EnemyMovement {
     pub speed: f32,
     pub angle: f32,
     pub state: EnemyMovementState,
 }
 
-#[derive(Debug, Clone, Component)]
+#[derive(Debug, Clone, Component, Deserialize, Serialize)]
 pub struct Formation {
     pub radius: (f32, f32),
     pub pivot: Point,
     pub start: Point,
 }
 
+// Enemy flies along one of the paths from the paths file
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PathFollow {
+    pub path: String,
+    #[serde(default)]
+    pub mirrored: bool, // flipped horizontally, side spawners pick a side when they spawn
+    #[serde(default)]
+    pub distance: f32, // how far along the path the enemy already is
+}
+
+// Swoops at the player along a curve and comes back to its slot, which is the last point
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DiveAttack {
+    pub points: Vec<Point>, // bezier control points
+    #[serde(default)]
+    pub distance: f32,
+    #[serde(skip)]
+    path: Option<Arc<EnemyPath>>,
+}
+
+impl DiveAttack {
+    pub fn new(points: Vec<Point>) -> Self {
+        let mut dive = DiveAttack {
+            points,
+            distance: 0.,
+            path: None,
+        };
+        dive.path();
+        dive
+    }
+
+    // Curve is only built once, a dive read back from a save builds it again
+    pub fn path(&mut self) -> Arc<EnemyPath> {
+        let points = &self.points;
+        self.path
+            .get_or_insert_with(|| {
+                let controls: Vec<Vec2> = points.iter().map(|p| Vec2::new(p.x, p.y)).collect();
+                Arc::new(EnemyPath::from_controls(
+                    PathCurve::Bezier,
+                    &controls,
+                    DIVE_SPEED.to_vec(),
+                    EnemyMovementState::Stationary,
+                ))
+            })
+            .clone()
+    }
+
+    pub fn slot(&self) -> Option<&Point> {
+        self.points.last()
+    }
+}
+
+// Flies down above the player, beams for a while and goes back to its slot
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TractorAttack {
+    pub slot: Point,
+    pub target: Point,
+    pub phase: TractorPhase,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum TractorPhase {
+    Descend,
+    Beam(f32), // seconds of beaming left
+    Return,
+}
+
+// Enemy can capture the player's ship with a tractor beam
+#[derive(Component)]
+pub struct TractorBeam;
+
+// Enemy is holding a captured ship, killing it gives the ship back
+#[derive(Component)]
+pub struct Captive;
+
+// Captured ship carried around by its captor
+#[derive(Component)]
+pub struct CapturedShip;
+
+// Beam below the enemy it belongs to
+#[derive(Component)]
+pub struct TractorBeamSprite(pub Entity);
+
 // Stat components
 
-#[derive(Component, Clone, Debug, Default)]
+#[derive(Component, Clone, Debug, Default, Deserialize, Serialize)]
 pub struct EnemyStats {
-    pub health: usize,
+    pub health: f32,
     pub spawn_rate: f32,  // from 0 to 1
     pub firing_rate: f32, // from 0 to 1
+    pub score: u32,
+}
+
+#[derive(Component, Clone, Debug, Deserialize)]
+pub struct EnemyWeapon {
+    pub fire_chance: f64, // chance to fire per frame
+    pub laser_speed: f32,
+    #[serde(default = "default_damage")]
+    pub damage: f32,
+    #[serde(default)]
+    pub pattern: BulletPattern,
+    #[serde(default)]
+    pub clash: ProjectileClash, // against player projectiles
+    #[serde(skip)]
+    pub volleys: u32, // fired so far, lets patterns change from one volley to the next
+}
+
+fn default_damage() -> f32 {
+    1.
 }
 
+// Enemy laser that bursts into another pattern once the delay runs out
+#[derive(Component, Clone, Debug, Deserialize, Serialize)]
+pub struct SplitShot {
+    pub delay: f32, // seconds left
+    pub pattern: BulletPattern,
+    pub shot: ShotStats,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DropEntry {
+    pub pickup: PickupKind,
+    pub chance: f64, // from 0 to 1
+}
+
+#[derive(Component, Clone, Debug, Default)]
+pub struct EnemyDrops(pub Vec<DropEntry>);
+
 // AI components
 
 #[derive(Component)]
@@ -0,0 +1,276 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::shared::resources::WinSize;
+use crate::stage_2_gameplay::components::{
+    DespawnEntity, DualFighter, Invincibility, Player, PlayerHit, Point,
+};
+use crate::stage_2_gameplay::constants::{
+    DIVE_CHANCE, DIVE_MAX, PLAYER_RESPAWN_DELAY, PLAYER_SIZE, TRACTOR_BEAM_SIZE,
+};
+use crate::stage_2_gameplay::resources::{
+    Director, GameRng, GameTextures, PlayerRespawn, PlayerState, RescuedShip, TimeScale,
+};
+
+use super::components::{
+    Captive, CapturedShip, DiveAttack, EnemyKilled, EnemyMovement, EnemyMovementState, Minion,
+    TractorAttack, TractorBeam, TractorBeamSprite, TractorPhase,
+};
+
+// Every now and then a minion waiting in its slot breaks off to attack the player
+pub fn dive_start_system(
+    win_size: Res<WinSize>,
+    director: Res<Director>,
+    time_scale: Res<TimeScale>,
+    mut rng: ResMut<GameRng>,
+    player_query: Query<&Transform, With<Player>>,
+    mut enemy_query: Query<
+        (
+            &Transform,
+            &mut EnemyMovement,
+            Option<&TractorBeam>,
+            Option<&Captive>,
+        ),
+        (With<Minion>, Without<Player>),
+    >,
+) {
+    let player = match player_query.get_single() {
+        Ok(player_tf) => player_tf.translation.truncate(),
+        Err(_) => return,
+    };
+
+    let chance = (DIVE_CHANCE * (director.tension * time_scale.0) as f64).min(1.);
+    if !rng.0.gen_bool(chance) {
+        return;
+    }
+
+    let attacking = enemy_query
+        .iter()
+        .filter(|(_, movement, ..)| is_attacking(&movement.state))
+        .count();
+    if attacking >= DIVE_MAX {
+        return;
+    }
+
+    // only slots inside the window, an attack shouldn't come out of nowhere
+    let mut waiting: Vec<_> = enemy_query
+        .iter_mut()
+        .filter(|(tf, movement, ..)| {
+            matches!(movement.state, EnemyMovementState::Stationary)
+                && tf.translation.x.abs() < win_size.w / 2.
+                && tf.translation.y.abs() < win_size.h / 2.
+        })
+        .collect();
+    if waiting.is_empty() {
+        return;
+    }
+
+    let index = rng.0.gen_range(0..waiting.len());
+    let (tf, mut movement, tractor, captive) = waiting.swap_remove(index);
+    let slot = tf.translation.truncate();
+
+    movement.state = if tractor.is_some() && captive.is_none() {
+        // stops high enough for the beam to reach the player
+        EnemyMovementState::Tractor(TractorAttack {
+            slot: point(slot),
+            target: point(Vec2::new(player.x, player.y + TRACTOR_BEAM_SIZE.1 * 0.8)),
+            phase: TractorPhase::Descend,
+        })
+    } else {
+        EnemyMovementState::Dive(DiveAttack::new(dive_points(slot, player)))
+    };
+}
+
+fn is_attacking(state: &EnemyMovementState) -> bool {
+    matches!(
+        state,
+        EnemyMovementState::Dive(_) | EnemyMovementState::Tractor(_)
+    )
+}
+
+fn point(position: Vec2) -> Point {
+    Point {
+        x: position.x,
+        y: position.y,
+    }
+}
+
+// Swings out away from the player, sweeps down past them and loops back up into the slot
+fn dive_points(slot: Vec2, player: Vec2) -> Vec<Point> {
+    let side = if slot.x < player.x { -1. } else { 1. };
+    let bottom = Vec2::new(player.x, player.y + 90.);
+
+    [
+        slot,
+        slot + Vec2::new(side * 100., 80.),
+        Vec2::new(player.x + side * 160., slot.y),
+        bottom,
+        bottom + Vec2::new(-side * 160., -30.),
+        slot + Vec2::new(-side * 120., -160.),
+        slot,
+    ]
+    .into_iter()
+    .map(point)
+    .collect()
+}
+
+fn is_beaming(movement: &EnemyMovement) -> bool {
+    matches!(
+        movement.state,
+        EnemyMovementState::Tractor(TractorAttack {
+            phase: TractorPhase::Beam(_),
+            ..
+        })
+    )
+}
+
+fn beam_translation(captor: Vec3) -> Vec3 {
+    Vec3::new(captor.x, captor.y - TRACTOR_BEAM_SIZE.1 / 2., 5.)
+}
+
+pub fn tractor_beam_sprite_system(
+    mut commands: Commands,
+    mut ev_despawn: EventWriter<DespawnEntity>,
+    captor_query: Query<(Entity, &Transform, &EnemyMovement), With<TractorBeam>>,
+    mut beam_query: Query<(Entity, &TractorBeamSprite, &mut Transform), Without<TractorBeam>>,
+) {
+    // beams follow their captor and go away once it stops beaming
+    for (entity, beam, mut beam_tf) in beam_query.iter_mut() {
+        match captor_query.get(beam.0) {
+            Ok((_, captor_tf, movement)) if is_beaming(movement) => {
+                beam_tf.translation = beam_translation(captor_tf.translation);
+            }
+            _ => ev_despawn.send(DespawnEntity { entity }),
+        }
+    }
+
+    for (captor, captor_tf, movement) in captor_query.iter() {
+        let has_beam = beam_query.iter().any(|(_, beam, _)| beam.0 == captor);
+        if !is_beaming(movement) || has_beam {
+            continue;
+        }
+
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0.4, 0.8, 1., 0.35),
+                    custom_size: Some(Vec2::new(TRACTOR_BEAM_SIZE.0, TRACTOR_BEAM_SIZE.1)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(beam_translation(captor_tf.translation)),
+                ..Default::default()
+            })
+            .insert(TractorBeamSprite(captor));
+    }
+}
+
+// Player caught in a beam loses the ship, the captor carries it back to its slot
+pub fn tractor_capture_system(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    mut player_state: ResMut<PlayerState>,
+    mut ev_despawn: EventWriter<DespawnEntity>,
+    mut ev_hit: EventWriter<PlayerHit>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<Invincibility>)>,
+    mut captor_query: Query<
+        (Entity, &Transform, &mut EnemyMovement),
+        (With<TractorBeam>, Without<Captive>, Without<Player>),
+    >,
+) {
+    let (player_entity, player_tf) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    let player = player_tf.translation;
+
+    for (captor, captor_tf, mut movement) in captor_query.iter_mut() {
+        if !is_beaming(&movement) {
+            continue;
+        }
+
+        let beam = captor_tf.translation;
+        let in_beam = (player.x - beam.x).abs() < TRACTOR_BEAM_SIZE.0 / 2.
+            && player.y < beam.y
+            && player.y > beam.y - TRACTOR_BEAM_SIZE.1;
+        if !in_beam {
+            continue;
+        }
+
+        if let EnemyMovementState::Tractor(attack) = &mut movement.state {
+            attack.phase = TractorPhase::Return;
+        }
+
+        capture_ship(&mut commands, &game_textures, captor);
+
+        // losing the ship costs a life just like getting shot down
+        player_state.lose_life();
+        if player_state.lives > 0 {
+            commands.insert_resource(PlayerRespawn(Timer::from_seconds(
+                PLAYER_RESPAWN_DELAY,
+                false,
+            )));
+        }
+        ev_hit.send(PlayerHit { damage: 0. });
+        ev_despawn.send(DespawnEntity {
+            entity: player_entity,
+        });
+
+        return;
+    }
+}
+
+// Captor holds on to the player's ship until it's shot down
+pub fn capture_ship(commands: &mut Commands, game_textures: &GameTextures, captor: Entity) {
+    commands
+        .entity(captor)
+        .insert(Captive)
+        .with_children(|parent| {
+            // hangs upside down above the captor
+            parent
+                .spawn_bundle(SpriteBundle {
+                    texture: game_textures.player.clone(),
+                    sprite: Sprite {
+                        color: Color::rgb(1., 0.4, 0.4),
+                        ..Default::default()
+                    },
+                    transform: Transform {
+                        translation: Vec3::new(0., PLAYER_SIZE.1, 0.),
+                        rotation: Quat::from_rotation_z(PI),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(CapturedShip);
+        });
+}
+
+// Shooting down a captor gives its captured ship back, it joins the player as a second fighter
+pub fn rescue_system(
+    mut commands: Commands,
+    mut ev_killed: EventReader<EnemyKilled>,
+    captive_query: Query<(), With<Captive>>,
+    player_query: Query<Entity, With<Player>>,
+) {
+    for kill in ev_killed.iter() {
+        if captive_query.get(kill.entity).is_err() {
+            continue;
+        }
+
+        match player_query.get_single() {
+            Ok(player) => {
+                commands.entity(player).insert(DualFighter);
+            }
+            // waits for the player's next ship
+            Err(_) => commands.insert_resource(RescuedShip),
+        }
+    }
+}
+
+pub fn rescued_ship_system(mut commands: Commands, player_query: Query<Entity, With<Player>>) {
+    if let Ok(player) = player_query.get_single() {
+        commands.entity(player).insert(DualFighter);
+        commands.remove_resource::<RescuedShip>();
+    }
+}
@@ -1,55 +1,109 @@
 use std::f32::consts::PI;
 
 use bevy::prelude::*;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
-use crate::{stage_2_gameplay::{
-    components::{
-        DespawnEntity, EntityType, ExplosionToSpawn, FromEntity, IsHit, Laser, Movable, SpriteSize,
-        Velocity,
+use crate::{
+    shared::resources::Difficulty,
+    stage_2_gameplay::{
+        components::{
+            Damage, DespawnEntity, EntityType, ExplosionToSpawn, FromEntity, Laser, Movable,
+            OffscreenPolicy, Player, ProjectileClash, SpriteSize, Velocity,
+        },
+        constants::{DIVE_FIRE_MULTIPLIER, ENEMY_LASER_SIZE, SPRITE_SCALE},
+        resources::{DifficultyStats, Director, GameRng, GameTextures, TimeScale},
     },
-    constants::{ENEMY_LASER_SIZE, SPRITE_SCALE},
-    resources::GameTextures,
-}, shared::resources::WinSize};
+};
 
-use super::components::{EnemyStats, Minion};
+use super::components::{EnemyMovement, EnemyMovementState, EnemyStats, EnemyWeapon};
+use super::pattern::{fire_pattern, PatternContext, ShotStats};
 
+#[allow(clippy::too_many_arguments)]
 pub fn minion_fire_system(
     mut commands: Commands,
     game_textures: Res<GameTextures>,
-    enemy_query: Query<&Transform, With<Minion>>,
+    mut rng: ResMut<GameRng>,
+    difficulty: Res<Difficulty>,
+    director: Res<Director>,
+    time_scale: Res<TimeScale>,
+    player_query: Query<&Transform, With<Player>>,
+    mut enemy_query: Query<(&Transform, &mut EnemyWeapon, Option<&EnemyMovement>), Without<Player>>,
 ) {
-    for &tf in enemy_query.iter() {
-        if thread_rng().gen_bool(1. / 60.) {
-            let (x, y) = (tf.translation.x, tf.translation.y);
-
-            // spawn minion laser
-            commands
-                .spawn_bundle(SpriteBundle {
-                    texture: game_textures.enemy_laser.clone(),
-                    transform: Transform {
-                        translation: Vec3::new(x, y - 15., 0.),
-                        rotation: Quat::from_rotation_x(PI),
-                        scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                })
-                .insert(Laser)
-                .insert(SpriteSize::from(ENEMY_LASER_SIZE))
-                .insert(FromEntity::FromEnemy)
-                .insert(Movable { auto_despawn: true })
-                .insert(Velocity { x: 0., y: -1. });
+    let target = player_query
+        .get_single()
+        .ok()
+        .map(|player_tf| player_tf.translation.truncate());
+    let stats = DifficultyStats::from(*difficulty);
+    let aggression = stats.fire_chance * (director.tension * time_scale.0) as f64;
+
+    for (&tf, mut weapon, movement) in enemy_query.iter_mut() {
+        // diving minions fire a lot more on their way past the player
+        let diving = matches!(
+            movement,
+            Some(EnemyMovement {
+                state: EnemyMovementState::Dive(_),
+                ..
+            })
+        );
+        let multiplier = if diving { DIVE_FIRE_MULTIPLIER } else { 1. };
+
+        let fire_chance = (weapon.fire_chance * aggression * multiplier).min(1.);
+        if rng.0.gen_bool(fire_chance) {
+            let context = PatternContext {
+                origin: Vec2::new(tf.translation.x, tf.translation.y - 15.),
+                target,
+                volley: weapon.volleys,
+            };
+
+            fire_pattern(
+                &mut commands,
+                &game_textures,
+                &weapon.pattern,
+                &context,
+                0.,
+                ShotStats {
+                    speed: weapon.laser_speed * stats.laser_speed,
+                    damage: weapon.damage,
+                    clash: weapon.clash,
+                },
+            );
+            weapon.volleys += 1;
         }
     }
 }
 
-pub fn minion_navigation_system(
-    mut commands: Commands,
-    win_size: Res<WinSize>,
-    mut query: Query<&mut Transform, With<Minion>>,
-) {
-    for (mut tf) in query.iter_mut() {
-        println!("");
-    }
+pub fn spawn_enemy_laser(
+    commands: &mut Commands,
+    game_textures: &GameTextures,
+    translation: Vec3,
+    velocity: Vec2,
+    damage: f32,
+    clash: ProjectileClash,
+) -> Entity {
+    // flipped so it points down, then turned towards where it's flying
+    let angle = velocity.x.atan2(-velocity.y);
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: game_textures.enemy_laser.clone(),
+            transform: Transform {
+                translation,
+                rotation: Quat::from_rotation_z(angle) * Quat::from_rotation_x(PI),
+                scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(Laser)
+        .insert(Damage(damage))
+        .insert(clash)
+        .insert(SpriteSize::from(ENEMY_LASER_SIZE))
+        .insert(FromEntity::FromEnemy)
+        .insert(Movable)
+        .insert(OffscreenPolicy::default())
+        .insert(Velocity {
+            x: velocity.x,
+            y: velocity.y,
+        })
+        .id()
 }
@@ -2,31 +2,52 @@ use std::f32::consts::PI;
 use std::time::Duration;
 
 use bevy::prelude::*;
-use iyes_loopless::prelude::{ConditionSet, FixedTimestepStage, IntoConditionalSystem};
-use rand::{thread_rng, Rng};
+use iyes_loopless::prelude::{
+    AppLooplessStateExt, ConditionHelpers, ConditionSet, FixedTimestepStage, IntoConditionalSystem,
+};
+use rand::Rng;
 
-use super::components::{DespawnEntity, EntityType, ExplosionToSpawn, IsHit, IsHittable};
+use super::components::{
+    Animation, Damage, DamageEvent, DespawnEntity, EntityType, ExplosionToSpawn, HitFlash,
+    IsHittable, Knockback, OffscreenPolicy, ParticleConfig, ParticleEmitter, ParticlesToSpawn,
+    PickupToSpawn, Point,
+};
 use super::constants::{
-    BASE_SPEED, ENEMY_LASER_SIZE, ENEMY_MAX, ENEMY_SIZE, ENEMY_SPAWN, HIT_DETECTION,
-    HIT_PROCESSING, SPRITE_SCALE,
+    ASTEROID_ARCHETYPE, BOSS_ARCHETYPE, BOSS_WAVES, CAPTOR_ARCHETYPE, CAPTOR_CHANCE, CAPTOR_WAVE,
+    ENEMY_SPAWN, HIT_DAMAGE, HIT_PROCESSING, HIT_STOP_TIME, KNOCKBACK_SPEED, LOOPER_ARCHETYPE,
+    LOOPER_CHANCE, LOOPER_WAVE, MINION_ARCHETYPE, MOVEMENT, SHAKE_BIG_EXPLOSION, SPRITE_SCALE,
+    SWOOPER_ARCHETYPE, SWOOPER_CHANCE, SWOOPER_WAVE,
 };
-use super::resources::GameTextures;
-use crate::shared::resources::{AppState, WinSize};
-use crate::stage_2_gameplay::components::{Laser, Movable, Point, SpriteSize, Velocity};
+use super::resources::{
+    CameraShake, DifficultyStats, Director, GameRng, GodMode, HitStop, RescuedShip, Wave,
+};
+use crate::shared::components::GameRunning;
+use crate::shared::resources::{AppState, Difficulty, WinSize};
+use crate::stage_2_gameplay::components::SpriteSize;
 
-use self::asteroid::asteroid_collision_system;
+use self::archetype::{EnemyRegistry, RegisteredEnemy};
 use self::components::{
-    Asteroid, Enemy, EnemyBundle, EnemyCount, EnemyMovement, EnemyMovementState, EnemyStats,
-    Formation, Minion, SpawningDirection,
+    Asteroid, Boss, Enemy, EnemyBundle, EnemyCount, EnemyDrops, EnemyKilled, EnemyKind,
+    EnemyMovement, EnemyMovementState, EnemySpawnEvent, EnemyStats, Minion, PathFollow, SpawnEnemy,
+    SpawningDirection, TractorBeam,
+};
+use self::dive::{
+    dive_start_system, rescue_system, rescued_ship_system, tractor_beam_sprite_system,
+    tractor_capture_system,
 };
 use self::minion::minion_fire_system;
-use self::motion::{calculate_spawning_point, enemy_movement_system};
+use self::motion::{calculate_spawning_point, enemy_movement_system, formation_slot};
+use self::path::EnemyPaths;
+use self::pattern::split_shot_system;
 
-pub mod asteroid;
+pub mod archetype;
 pub mod components;
+pub mod dive;
 pub mod formation;
 pub mod minion;
 pub mod motion;
+pub mod path;
+pub mod pattern;
 
 pub struct EnemyPlugin;
 
@@ -40,8 +61,12 @@ impl Plugin for EnemyPlugin {
                 .into(),
         );
 
-        app
-            // .add_event::<EnemySpawnEvent>()
+        app.add_event::<EnemySpawnEvent>()
+            .add_event::<EnemyKilled>()
+            .add_enter_system(
+                AppState::Gameplay,
+                enemy_registry_setup_system.run_unless_resource_exists::<GameRunning>(),
+            )
             .add_stage_before(
                 CoreStage::Update,
                 ENEMY_SPAWN,
@@ -50,180 +75,422 @@ impl Plugin for EnemyPlugin {
             .add_system_set(
                 ConditionSet::new()
                     .run_in_state(AppState::Gameplay)
+                    .run_unless_resource_exists::<HitStop>()
+                    .label(MOVEMENT)
+                    .with_system(enemy_spawner_system)
                     .with_system(enemy_movement_system)
                     .with_system(minion_fire_system)
+                    .with_system(split_shot_system)
                     .into(),
             )
-            // Collision processing systems
-            // They might end up removing entities so they must be executed in order
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(AppState::Gameplay)
+                    .run_unless_resource_exists::<HitStop>()
+                    .with_system(dive_start_system)
+                    .with_system(tractor_beam_sprite_system)
+                    .into(),
+            )
+            // runs before the hits so a captor shot down in the same frame is still around
             .add_system(
-                asteroid_collision_system
+                tractor_capture_system
                     .run_in_state(AppState::Gameplay)
-                    .label(HIT_DETECTION),
+                    .run_unless_resource_exists::<HitStop>()
+                    .run_unless_resource_exists::<GodMode>()
+                    .before(HIT_PROCESSING),
             )
+            .add_system(
+                rescue_system
+                    .run_in_state(AppState::Gameplay)
+                    .after(HIT_PROCESSING),
+            )
+            .add_system(
+                rescued_ship_system
+                    .run_in_state(AppState::Gameplay)
+                    .run_if_resource_exists::<RescuedShip>(),
+            )
+            // kills keep counting during hit stop so no event gets lost, every few waves start
+            // with a boss
+            .add_system(
+                wave_system
+                    .run_in_state(AppState::Gameplay)
+                    .after(HIT_PROCESSING),
+            )
+            // Collision processing systems
+            // They might end up removing entities so they must be executed in order
             .add_system(
                 enemy_hit_system
                     .run_in_state(AppState::Gameplay)
                     .label(HIT_PROCESSING)
-                    .after(HIT_DETECTION),
+                    .after(HIT_DAMAGE),
             );
     }
 }
 
-// fn enemy_spawn_system(
-//     mut commands: Commands,
-//     mut ev_spawn: EventWriter<EnemySpawnEvent>,
-//     game_textures: Res<GameTextures>,
-//     mut enemy_count: ResMut<EnemyCount>,
-//     win_size: Res<WinSize>,
-// ) {
-//     let spawn_enemy: Option<SpawnEnemy> = if enemy_count.asteroids < ENEMY_MAX {
-//         let starting_point = calculate_spawning_point(SpawningDirection::Top, &win_size);
-
-//         Some(SpawnEnemy {
-//             bundle: EnemyBundle {
-//                 stats: EnemyStats {
-//                     health: 1,
-//                     firing_rate: 1.,
-//                     spawn_rate: 1.,
-//                 },
-//                 movement: EnemyMovement {
-//                     speed: BASE_SPEED,
-//                     state: EnemyMovementState::Downward,
-//                     angle: -PI / 2.,
-//                 },
-//                 enemy_type: EntityType::Asteroid,
-//             },
-//             starting_point: starting_point,
-//             texture: game_textures.enemy.clone(),
-//         })
-//     } else if enemy_count.minions < ENEMY_MAX {
-//     } else {}
-// }
+fn enemy_registry_setup_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    win_size: Res<WinSize>,
+) {
+    commands.insert_resource(EnemyRegistry::load(&asset_server));
+    commands.insert_resource(EnemyPaths::load(&win_size));
+}
 
 fn enemy_spawn_system(
+    mut ev_spawn: EventWriter<EnemySpawnEvent>,
+    enemy_count: Res<EnemyCount>,
+    difficulty: Res<Difficulty>,
+    director: Res<Director>,
+    wave: Res<Wave>,
+    mut rng: ResMut<GameRng>,
+) {
+    // low tension also gives the player some breathing room between spawns
+    if !rng.0.gen_bool(director.tension.min(1.) as f64) {
+        return;
+    }
+
+    let enemy_max = director.enemy_max(DifficultyStats::from(*difficulty).enemy_max);
+
+    let mut send_spawn = |archetype: &str| {
+        ev_spawn.send(EnemySpawnEvent(SpawnEnemy {
+            archetype: archetype.to_string(),
+            starting_point: None,
+        }));
+    };
+
+    if enemy_count.asteroids < enemy_max {
+        send_spawn(ASTEROID_ARCHETYPE);
+    }
+    if enemy_count.minions < enemy_max {
+        // later waves mix in captors that try to steal the player's ship and minions with their
+        // own paths and bullet patterns
+        if wave.number >= CAPTOR_WAVE && rng.0.gen_bool(CAPTOR_CHANCE) {
+            send_spawn(CAPTOR_ARCHETYPE);
+        } else if wave.number >= LOOPER_WAVE && rng.0.gen_bool(LOOPER_CHANCE) {
+            send_spawn(LOOPER_ARCHETYPE);
+        } else if wave.number >= SWOOPER_WAVE && rng.0.gen_bool(SWOOPER_CHANCE) {
+            send_spawn(SWOOPER_ARCHETYPE);
+        } else {
+            send_spawn(MINION_ARCHETYPE);
+        }
+    }
+}
+
+fn enemy_spawner_system(
     mut commands: Commands,
-    game_textures: Res<GameTextures>,
-    mut enemy_count: ResMut<EnemyCount>,
+    mut ev_spawn: EventReader<EnemySpawnEvent>,
+    registry: Res<EnemyRegistry>,
+    paths: Res<EnemyPaths>,
+    mut rng: ResMut<GameRng>,
     win_size: Res<WinSize>,
+    difficulty: Res<Difficulty>,
 ) {
-    if enemy_count.asteroids < ENEMY_MAX {
-        let starting_point = calculate_spawning_point(SpawningDirection::Top, &win_size);
-        let (x, y) = (starting_point.x, starting_point.y);
-
-        commands
-            .spawn_bundle(SpriteBundle {
-                texture: game_textures.enemy.clone(),
-                transform: Transform {
-                    translation: Vec3::new(x, y, 10.),
-                    scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
-                    ..Default::default()
-                },
-                ..Default::default()
-            })
-            .insert_bundle(EnemyBundle {
-                movement: EnemyMovement {
-                    speed: BASE_SPEED,
-                    state: EnemyMovementState::Downward,
-                    angle: -PI / 2.,
-                },
-                stats: EnemyStats {
-                    health: 1,
-                    firing_rate: 1.,
-                    spawn_rate: 1.,
+    for EnemySpawnEvent(spawn) in ev_spawn.iter() {
+        let registered = match registry.get(&spawn.archetype) {
+            Some(registered) => registered,
+            None => {
+                warn!("Unknown enemy archetype: {}", spawn.archetype);
+                continue;
+            }
+        };
+
+        // enemies waiting in formation fly in to their slot first, side spawners following a path
+        // enter from either side
+        let movement = match &registered.archetype.movement {
+            EnemyMovementState::Stationary if spawn.starting_point.is_none() => {
+                EnemyMovementState::Travel(formation_slot(&win_size, &mut rng.0))
+            }
+            EnemyMovementState::FollowPath(follow)
+                if matches!(
+                    registered.archetype.spawn_direction,
+                    SpawningDirection::Sides
+                ) =>
+            {
+                EnemyMovementState::FollowPath(PathFollow {
+                    mirrored: rng.0.gen_bool(0.5),
+                    ..follow.clone()
+                })
+            }
+            movement => movement.clone(),
+        };
+
+        // enemies following a path show up where their path starts
+        let path_start = match &movement {
+            EnemyMovementState::FollowPath(follow) => paths
+                .get(&follow.path)
+                .map(|path| path.position(0., follow.mirrored)),
+            _ => None,
+        };
+
+        let starting_point = spawn
+            .starting_point
+            .clone()
+            .unwrap_or_else(|| match path_start {
+                Some(start) => Point {
+                    x: start.x,
+                    y: start.y,
                 },
-                enemy_type: EntityType::Asteroid,
-            })
-            .insert(Enemy)
-            .insert(Asteroid)
-            .insert(IsHittable)
-            .insert(SpriteSize::from(ENEMY_SIZE));
+                None => {
+                    let direction = registered.archetype.spawn_direction.clone();
+                    calculate_spawning_point(direction, &win_size, &mut rng.0)
+                }
+            });
 
-        enemy_count.asteroids += 1;
+        spawn_enemy(
+            &mut commands,
+            &spawn.archetype,
+            registered,
+            starting_point,
+            movement,
+            *difficulty,
+        );
     }
-    if enemy_count.minions < ENEMY_MAX {
-        let mut rng = thread_rng();
-
-        let starting_point = calculate_spawning_point(SpawningDirection::Top, &win_size);
-        let (x, y) = (starting_point.x, starting_point.y);
-        let angle: f32 = if x < 0. { PI } else { 0. };
-
-        // // compute the pivot point x/y
-        // let w_span = win_size.w / 4.;
-        // let h_span = win_size.h / 3. + 50.;
-        // let pivot = Point {
-        //     x: rng.gen_range(-w_span..w_span),
-        //     y: rng.gen_range(0.0..h_span),
-        // };
-
-        // // compute the radius
-        // let radius = (rng.gen_range(80.0..150.), 100.);
-
-        // // compute the start angle
-        // let angle = (y - pivot.y).atan2(x - pivot.x);
-
-        // let formation = Formation {
-        //     radius,
-        //     pivot,
-        //     start: starting_point,
-        // };
-
-        commands
-            .spawn_bundle(SpriteBundle {
-                texture: game_textures.enemy.clone(),
-                transform: Transform {
-                    translation: Vec3::new(x, y, 10.),
-                    scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
-                    ..Default::default()
-                },
-                ..Default::default()
-            })
-            .insert_bundle(EnemyBundle {
-                movement: EnemyMovement {
-                    speed: BASE_SPEED,
-                    state: EnemyMovementState::Stationary,
-                    angle,
-                },
-                stats: EnemyStats {
-                    health: 3,
-                    firing_rate: 1.,
-                    spawn_rate: 1.,
-                },
-                enemy_type: EntityType::Minion,
-            })
-            .insert(Enemy)
-            .insert(Minion)
-            .insert(IsHittable)
-            .insert(SpriteSize::from(ENEMY_SIZE));
+}
+
+// Builds the enemy described by the archetype moving the given way, returns it so callers can adjust
+// it further
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    name: &str,
+    registered: &RegisteredEnemy,
+    starting_point: Point,
+    movement: EnemyMovementState,
+    difficulty: Difficulty,
+) -> Entity {
+    let RegisteredEnemy { archetype, texture } = registered;
+    let (x, y) = (starting_point.x, starting_point.y);
+
+    // downward movers face down, everyone else faces away from the closest side
+    let angle: f32 = match movement {
+        EnemyMovementState::Downward => -PI / 2.,
+        _ if x < 0. => PI,
+        _ => 0.,
+    };
 
-        enemy_count.minions += 1;
+    let mut enemy = commands.spawn_bundle(SpriteBundle {
+        texture: texture.clone(),
+        transform: Transform {
+            translation: Vec3::new(x, y, 10.),
+            scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+
+    enemy
+        .insert_bundle(EnemyBundle {
+            movement: EnemyMovement {
+                speed: archetype.speed,
+                state: movement,
+                angle,
+            },
+            stats: EnemyStats {
+                health: archetype.health * DifficultyStats::from(difficulty).enemy_health,
+                firing_rate: 1.,
+                spawn_rate: 1.,
+                score: archetype.score,
+            },
+            enemy_type: archetype.entity_type.clone(),
+        })
+        .insert(Enemy)
+        .insert(IsHittable)
+        .insert(SpriteSize::from(archetype.size))
+        .insert(Damage(archetype.collision_damage))
+        .insert(EnemyDrops(archetype.drops.clone()))
+        .insert(OffscreenPolicy::default());
+
+    if let Some(weapon) = &archetype.weapon {
+        enemy.insert(weapon.clone());
+    }
+    if archetype.boss {
+        enemy.insert(Boss);
     }
+    if archetype.tractor {
+        enemy.insert(TractorBeam);
+    }
+    if let Some(clip) = &archetype.animation {
+        enemy.insert(Animation::new(clip));
+    }
+
+    match archetype.entity_type {
+        EntityType::Asteroid => {
+            enemy.insert(Asteroid);
+        }
+        EntityType::Minion => {
+            // minions face down so their engine trail goes up
+            enemy.insert(Minion).insert(ParticleEmitter::new(
+                ParticleConfig {
+                    direction: 0.,
+                    ..ParticleConfig::engine_trail()
+                },
+                25.,
+                Vec2::new(0., archetype.size.1 / 2. * SPRITE_SCALE),
+            ));
+        }
+        _ => {}
+    }
+
+    enemy.insert(EnemyKind(name.to_string()));
+    enemy.id()
 }
 
+#[allow(clippy::too_many_arguments)]
 fn enemy_hit_system(
     mut commands: Commands,
     mut ev_despawn: EventWriter<DespawnEntity>,
-    mut query: Query<
-        (Entity, &Transform, &mut EnemyStats, &EntityType),
-        (With<Enemy>, With<IsHit>),
-    >,
+    mut ev_killed: EventWriter<EnemyKilled>,
+    mut shake: ResMut<CameraShake>,
+    mut rng: ResMut<GameRng>,
+    mut ev_damage: EventReader<DamageEvent>,
+    mut query: Query<(
+        &Transform,
+        &mut EnemyStats,
+        &EntityType,
+        &EnemyDrops,
+        &Sprite,
+        Option<&mut HitFlash>,
+        Option<&Boss>,
+    )>,
 ) {
-    for (entity, entity_tf, mut entity_stats, entity_type) in query.iter_mut() {
-        if entity_stats.health != 0 {
-            entity_stats.health -= 1;
+    for ev in ev_damage.iter() {
+        let entity = ev.target;
+        let (entity_tf, mut entity_stats, entity_type, drops, sprite, flash, boss) =
+            match query.get_mut(entity) {
+                Ok(enemy) => enemy,
+                Err(_) => continue,
+            };
+
+        // already killed by an earlier hit of the same frame
+        if entity_stats.health <= 0. {
+            continue;
         }
+        entity_stats.health -= ev.damage;
 
-        if entity_stats.health == 0 {
-            ev_despawn.send(DespawnEntity {
-                entity: entity,
-                entity_type: entity_type.clone(),
-            });
+        if entity_stats.health <= 0. {
+            ev_despawn.send(DespawnEntity { entity });
 
             commands
                 .spawn()
                 .insert(ExplosionToSpawn(entity_tf.translation));
+
+            ev_killed.send(EnemyKilled {
+                entity,
+                translation: entity_tf.translation,
+                score: entity_stats.score,
+            });
+
+            if let EntityType::Asteroid = entity_type {
+                commands.spawn().insert(ParticlesToSpawn {
+                    config: ParticleConfig::debris(),
+                    count: 12,
+                    translation: entity_tf.translation,
+                });
+            }
+
+            // at most one pickup is dropped per kill
+            if let Some(drop) = drops.0.iter().find(|drop| rng.0.gen_bool(drop.chance)) {
+                commands
+                    .spawn()
+                    .insert(PickupToSpawn(drop.pickup, entity_tf.translation));
+            }
+
+            if boss.is_some() {
+                shake.add_trauma(SHAKE_BIG_EXPLOSION);
+                commands.insert_resource(HitStop(Timer::from_seconds(HIT_STOP_TIME, false)));
+            }
         } else {
-            commands.entity(entity).remove::<IsHit>();
+            // a sprite that's already flashing shows white, only the flash gets longer
+            match flash {
+                Some(mut flash) => flash.timer.reset(),
+                None => {
+                    commands.entity(entity).insert(HitFlash::new(sprite.color));
+                }
+            }
+
+            // enemies get pushed back up, away from the player
+            commands
+                .entity(entity)
+                .insert(Knockback(Vec2::new(0., KNOCKBACK_SPEED)));
+        }
+    }
+}
+
+fn wave_system(
+    mut ev_killed: EventReader<EnemyKilled>,
+    mut ev_spawn: EventWriter<EnemySpawnEvent>,
+    mut wave: ResMut<Wave>,
+) {
+    for _ in ev_killed.iter() {
+        let number = wave.number;
+        wave.register_kill();
+
+        if wave.number != number && wave.number.is_multiple_of(BOSS_WAVES) {
+            ev_spawn.send(EnemySpawnEvent(SpawnEnemy {
+                archetype: BOSS_ARCHETYPE.to_string(),
+                starting_point: None,
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+
+    use super::components::{EnemyDrops, EnemyStats};
+    use super::enemy_hit_system;
+    use crate::stage_2_gameplay::components::{DamageEvent, EntityType, HitFlash};
+    use crate::stage_2_gameplay::feedback::hit_flash_system;
+    use crate::stage_2_gameplay::testing::TestWorld;
+
+    const COLOR: Color = Color::rgb(0.2, 0.4, 0.6);
+
+    #[test]
+    fn repeated_hits_keep_the_original_color() {
+        let mut game = TestWorld::new();
+        game.add_system(enemy_hit_system)
+            .add_system(hit_flash_system);
+
+        let source = game.world.spawn().id();
+        let enemy = game
+            .world
+            .spawn()
+            .insert(Transform::default())
+            .insert(EnemyStats {
+                health: 10.,
+                ..Default::default()
+            })
+            .insert(EntityType::Minion)
+            .insert(EnemyDrops::default())
+            .insert(Sprite {
+                color: COLOR,
+                ..Default::default()
+            })
+            .id();
+
+        // second hit lands while the sprite is still flashing white
+        for _ in 0..2 {
+            game.send(DamageEvent {
+                source,
+                target: enemy,
+                contact: Vec3::ZERO,
+                damage: 1.,
+            });
+            game.frame();
+            game.frame();
+            assert_ne!(game.world.get::<Sprite>(enemy).unwrap().color, COLOR);
         }
+        assert_eq!(game.world.get::<HitFlash>(enemy).unwrap().color, COLOR);
+
+        game.world
+            .get_mut::<HitFlash>(enemy)
+            .unwrap()
+            .timer
+            .tick(Duration::from_secs(1));
+        game.frame();
+        assert_eq!(game.world.get::<Sprite>(enemy).unwrap().color, COLOR);
+        assert!(game.world.get::<HitFlash>(enemy).is_none());
     }
 }
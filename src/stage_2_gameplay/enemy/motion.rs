@@ -1,45 +1,62 @@
 use std::f32::consts::PI;
 
 use bevy::prelude::*;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 use crate::{
     shared::resources::WinSize,
     stage_2_gameplay::{
-        components::{Point, EntityType, DespawnEntity}, constants::TIME_STEP, enemy::components::EnemyMovementState,
+        components::Point,
+        constants::{TIME_STEP, TRACTOR_BEAM_TIME},
+        enemy::components::EnemyMovementState,
+        resources::TimeScale,
     },
 };
 
-use super::components::{Enemy, EnemyCount, EnemyMovement, SpawningDirection};
-
-pub fn calculate_spawning_point(spawn_direction: SpawningDirection, win_size: &WinSize) -> Point {
-    let mut rng = thread_rng();
+use super::components::{Enemy, EnemyCount, EnemyMovement, SpawningDirection, TractorPhase};
+use super::path::EnemyPaths;
 
+pub fn calculate_spawning_point(
+    spawn_direction: SpawningDirection,
+    win_size: &WinSize,
+    rng: &mut impl Rng,
+) -> Point {
     let mut w_span = win_size.w / 2. + 100.;
     let h_span = win_size.h / 2. + 100.;
 
     match spawn_direction {
         SpawningDirection::Top => {
             w_span -= 100.;
-            let x = rng.gen_range(-w_span..w_span) as f32;
+            let x = rng.gen_range(-w_span..w_span);
             let y = h_span;
             Point { x, y }
         }
         SpawningDirection::Sides => {
             let x = if rng.gen_bool(0.5) { w_span } else { -w_span };
-            let y = rng.gen_range(-h_span..h_span) as f32;
+            let y = rng.gen_range(-h_span..h_span);
             Point { x, y }
         }
     }
 }
 
+// Somewhere in the upper part of the window for an enemy to wait in formation
+pub fn formation_slot(win_size: &WinSize, rng: &mut impl Rng) -> Point {
+    let w_span = win_size.w / 2. - 60.;
+    Point {
+        x: rng.gen_range(-w_span..w_span),
+        y: rng.gen_range(win_size.h / 8.0..win_size.h / 2. - 60.),
+    }
+}
+
 pub fn enemy_movement_system(
     mut commands: Commands,
-    win_size: Res<WinSize>,
-    mut ev_despawn: EventWriter<DespawnEntity>,
-    mut query: Query<(Entity, &mut Transform, &mut EnemyMovement, &EntityType), With<Enemy>>,
+    time_scale: Res<TimeScale>,
+    paths: Res<EnemyPaths>,
+    mut query: Query<(&mut Transform, &mut EnemyMovement), With<Enemy>>,
 ) {
-    for (entity, mut transform, mut movement, entity_type) in query.iter_mut() {
+    let time_step = TIME_STEP * time_scale.0;
+
+    for (mut transform, mut movement) in query.iter_mut() {
         // current position
         let (x_org, y_org) = (transform.translation.x, transform.translation.y);
 
@@ -47,40 +64,27 @@ pub fn enemy_movement_system(
         let next_point: Point = match &movement.state {
             EnemyMovementState::Stationary => Point { x: x_org, y: y_org },
             EnemyMovementState::Downward => {
-                let distance = TIME_STEP * movement.speed;
+                let distance = time_step * movement.speed;
                 Point {
                     x: x_org,
                     y: y_org - distance,
                 }
             }
             EnemyMovementState::Travel(travel_point) => {
-                let (x_end, y_end) = (travel_point.x, travel_point.y);
-
                 // max distance in 1 sec
-                let max_distance = TIME_STEP * movement.speed;
-
-                // compute distance
-                let dx = x_org - x_end;
-                let dy = y_org - y_end;
-                let distance = (dx * dx + dy * dy).sqrt();
-                let distance_ratio = if distance != 0. {
-                    max_distance / distance
-                } else {
-                    0.
-                };
-
-                // compute final x/y
-                let x = x_org - dx * distance_ratio;
-                let x = if dx > 0. { x.max(x_end) } else { x.min(x_end) };
-                let y = y_org - dy * distance_ratio;
-                let y = if dy > 0. { y.max(y_end) } else { y.min(y_end) };
+                let max_distance = time_step * movement.speed;
+                let point = travel_towards(x_org, y_org, travel_point, max_distance);
 
-                Point { x, y }
+                // waits where it arrived
+                if point == *travel_point {
+                    movement.state = EnemyMovementState::Stationary;
+                }
+                point
             }
             EnemyMovementState::Seeking => Point { x: x_org, y: y_org },
             EnemyMovementState::CircleFormation(formation) => {
                 // max distance in 1 sec
-                let max_distance = TIME_STEP * movement.speed;
+                let max_distance = time_step * movement.speed;
 
                 // fixtures
                 let dir: f32 = if formation.start.x < 0. { 1. } else { -1. }; // 1 for counter clockwise and -1 clockwise
@@ -89,7 +93,7 @@ pub fn enemy_movement_system(
 
                 // compute next angle
                 let angle = movement.angle
-                    + dir * movement.speed * TIME_STEP / (x_radius.min(y_radius) * PI / 2.);
+                    + dir * movement.speed * time_step / (x_radius.min(y_radius) * PI / 2.);
 
                 // compute target x/y
                 let x_dst = x_radius * angle.cos() + x_pivot;
@@ -117,22 +121,108 @@ pub fn enemy_movement_system(
 
                 Point { x, y }
             }
+            EnemyMovementState::FollowPath(follow) => match paths.get(&follow.path) {
+                Some(path) => {
+                    let mut follow = follow.clone();
+                    follow.distance += time_step * movement.speed * path.speed(follow.distance);
+                    let position = path.position(follow.distance, follow.mirrored);
+
+                    // path hands over to the movement that comes after it
+                    movement.state = if follow.distance >= path.length() {
+                        path.then.clone()
+                    } else {
+                        EnemyMovementState::FollowPath(follow)
+                    };
+
+                    Point {
+                        x: position.x,
+                        y: position.y,
+                    }
+                }
+                None => {
+                    warn!("Unknown enemy path: {}", follow.path);
+                    movement.state = EnemyMovementState::Stationary;
+                    Point { x: x_org, y: y_org }
+                }
+            },
+            EnemyMovementState::Dive(dive) => {
+                let mut dive = dive.clone();
+                let path = dive.path();
+
+                dive.distance += time_step * movement.speed * path.speed(dive.distance);
+                let position = path.position(dive.distance, false);
+
+                // back in its slot
+                movement.state = if dive.distance >= path.length() {
+                    path.then.clone()
+                } else {
+                    EnemyMovementState::Dive(dive)
+                };
+
+                Point {
+                    x: position.x,
+                    y: position.y,
+                }
+            }
+            EnemyMovementState::Tractor(attack) => {
+                let mut attack = attack.clone();
+                let max_distance = time_step * movement.speed;
+
+                let point = match attack.phase {
+                    TractorPhase::Descend => {
+                        let point = travel_towards(x_org, y_org, &attack.target, max_distance);
+                        if point == attack.target {
+                            attack.phase = TractorPhase::Beam(TRACTOR_BEAM_TIME);
+                        }
+                        point
+                    }
+                    TractorPhase::Beam(time_left) => {
+                        let time_left = time_left - time_step;
+                        attack.phase = if time_left > 0. {
+                            TractorPhase::Beam(time_left)
+                        } else {
+                            TractorPhase::Return
+                        };
+                        Point { x: x_org, y: y_org }
+                    }
+                    TractorPhase::Return => {
+                        travel_towards(x_org, y_org, &attack.slot, max_distance)
+                    }
+                };
+
+                movement.state = match attack.phase {
+                    TractorPhase::Return if point == attack.slot => EnemyMovementState::Stationary,
+                    _ => EnemyMovementState::Tractor(attack),
+                };
+
+                point
+            }
         };
 
         let translation = &mut transform.translation;
         (translation.x, translation.y) = (next_point.x, next_point.y);
-
-        // TODO create a more universal way to despawn enemies when it's outside the window
-        const MARGIN: f32 = 200.;
-        if translation.y > win_size.h / 2. + MARGIN
-            || translation.y < -win_size.h / 2. - MARGIN
-            || translation.x > win_size.w / 2. + MARGIN
-            || translation.x < -win_size.w / 2. - MARGIN
-        {
-            ev_despawn.send(DespawnEntity {
-                entity: entity,
-                entity_type: entity_type.clone(),
-            });
-        }
     }
 }
+
+// Straight towards the end point without overshooting it
+fn travel_towards(x_org: f32, y_org: f32, end: &Point, max_distance: f32) -> Point {
+    let (x_end, y_end) = (end.x, end.y);
+
+    // compute distance
+    let dx = x_org - x_end;
+    let dy = y_org - y_end;
+    let distance = (dx * dx + dy * dy).sqrt();
+    let distance_ratio = if distance != 0. {
+        max_distance / distance
+    } else {
+        0.
+    };
+
+    // compute final x/y
+    let x = x_org - dx * distance_ratio;
+    let x = if dx > 0. { x.max(x_end) } else { x.min(x_end) };
+    let y = y_org - dy * distance_ratio;
+    let y = if dy > 0. { y.max(y_end) } else { y.min(y_end) };
+
+    Point { x, y }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::shared::general::load_asset_data;
+use crate::shared::resources::WinSize;
+use crate::stage_2_gameplay::constants::{ENEMY_PATHS, PATH_SEGMENT_STEPS};
+
+use super::components::EnemyMovementState;
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum PathCurve {
+    // chained cubic segments, every segment adds two control points and an end point
+    Bezier,
+    // smooth curve going through every point
+    CatmullRom,
+}
+
+// Path before it's flattened, points are relative to the window with (-1, -1) the bottom left
+// corner and (1, 1) the top right
+#[derive(Clone, Debug, Deserialize)]
+pub struct PathDefinition {
+    pub curve: PathCurve,
+    pub points: Vec<(f32, f32)>,
+    #[serde(default)]
+    pub speed: Vec<f32>, // speed multipliers spread evenly over the path
+    #[serde(default)]
+    pub then: EnemyMovementState, // movement once the end of the path is reached
+}
+
+// Path flattened into short straight pieces so it can be followed at an even speed
+#[derive(Debug)]
+pub struct EnemyPath {
+    points: Vec<Vec2>,
+    distances: Vec<f32>, // distance along the path at every point
+    speed: Vec<f32>,
+    pub then: EnemyMovementState,
+}
+
+impl EnemyPath {
+    pub fn new(definition: &PathDefinition, win_size: &WinSize) -> Self {
+        let half_size = Vec2::new(win_size.w / 2., win_size.h / 2.);
+        let controls: Vec<Vec2> = definition
+            .points
+            .iter()
+            .map(|&(x, y)| Vec2::new(x, y) * half_size)
+            .collect();
+
+        EnemyPath::from_controls(
+            definition.curve,
+            &controls,
+            definition.speed.clone(),
+            definition.then.clone(),
+        )
+    }
+
+    // Path from points already in window coordinates, ie. built on the fly for a dive
+    pub fn from_controls(
+        curve: PathCurve,
+        controls: &[Vec2],
+        speed: Vec<f32>,
+        then: EnemyMovementState,
+    ) -> Self {
+        let points = match curve {
+            PathCurve::Bezier => bezier(controls),
+            PathCurve::CatmullRom => catmull_rom(controls),
+        };
+
+        let mut distances = Vec::with_capacity(points.len());
+        let mut distance = 0.;
+        for (i, point) in points.iter().enumerate() {
+            if i > 0 {
+                distance += point.distance(points[i - 1]);
+            }
+            distances.push(distance);
+        }
+
+        EnemyPath {
+            points,
+            distances,
+            speed,
+            then,
+        }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.distances.last().copied().unwrap_or(0.)
+    }
+
+    // Mirrored paths are flipped horizontally so one path can enter from either side
+    pub fn position(&self, distance: f32, mirrored: bool) -> Vec2 {
+        let i = self.distances.partition_point(|&d| d < distance);
+        let position = if i == 0 {
+            self.points[0]
+        } else if i >= self.points.len() {
+            self.points[self.points.len() - 1]
+        } else {
+            let (start, end) = (self.distances[i - 1], self.distances[i]);
+            let t = (distance - start) / (end - start).max(f32::EPSILON);
+            self.points[i - 1].lerp(self.points[i], t)
+        };
+
+        if mirrored {
+            Vec2::new(-position.x, position.y)
+        } else {
+            position
+        }
+    }
+
+    pub fn speed(&self, distance: f32) -> f32 {
+        match self.speed.len() {
+            0 => 1.,
+            1 => self.speed[0],
+            len => {
+                let progress = (distance / self.length().max(f32::EPSILON)).clamp(0., 1.);
+                let index = progress * (len - 1) as f32;
+                let i = (index.floor() as usize).min(len - 2);
+                let t = index - i as f32;
+                self.speed[i] + (self.speed[i + 1] - self.speed[i]) * t
+            }
+        }
+    }
+}
+
+fn bezier(controls: &[Vec2]) -> Vec<Vec2> {
+    let mut points = controls.first().copied().into_iter().collect::<Vec<_>>();
+
+    for segment in controls.windows(4).step_by(3) {
+        let (p0, p1, p2, p3) = (segment[0], segment[1], segment[2], segment[3]);
+        for step in 1..=PATH_SEGMENT_STEPS {
+            let t = step as f32 / PATH_SEGMENT_STEPS as f32;
+            let u = 1. - t;
+            points
+                .push(p0 * u * u * u + p1 * 3. * u * u * t + p2 * 3. * u * t * t + p3 * t * t * t);
+        }
+    }
+
+    points
+}
+
+fn catmull_rom(controls: &[Vec2]) -> Vec<Vec2> {
+    let mut points = controls.first().copied().into_iter().collect::<Vec<_>>();
+
+    // the first and last point double as their own neighbours
+    for i in 0..controls.len().saturating_sub(1) {
+        let p0 = controls[i.saturating_sub(1)];
+        let (p1, p2) = (controls[i], controls[i + 1]);
+        let p3 = controls[(i + 2).min(controls.len() - 1)];
+
+        for step in 1..=PATH_SEGMENT_STEPS {
+            let t = step as f32 / PATH_SEGMENT_STEPS as f32;
+            let (t2, t3) = (t * t, t * t * t);
+            points.push(
+                0.5 * (p1 * 2.
+                    + (p2 - p0) * t
+                    + (p0 * 2. - p1 * 5. + p2 * 4. - p3) * t2
+                    + (p3 - p0 + p1 * 3. - p2 * 3.) * t3),
+            );
+        }
+    }
+
+    points
+}
+
+// Resource - every known enemy path by name, already scaled to the window
+#[derive(Default)]
+pub struct EnemyPaths(HashMap<String, EnemyPath>);
+
+impl EnemyPaths {
+    pub fn load(win_size: &WinSize) -> Self {
+        let definitions: HashMap<String, PathDefinition> = load_asset_data(ENEMY_PATHS);
+
+        let paths = definitions
+            .into_iter()
+            .filter_map(|(name, definition)| {
+                if definition.points.is_empty() {
+                    warn!("Enemy path without points: {}", name);
+                    return None;
+                }
+                let path = EnemyPath::new(&definition, win_size);
+                Some((name, path))
+            })
+            .collect();
+
+        EnemyPaths(paths)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EnemyPath> {
+        self.0.get(name)
+    }
+}
@@ -0,0 +1,208 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::stage_2_gameplay::components::{DespawnEntity, Player, ProjectileClash, Velocity};
+use crate::stage_2_gameplay::constants::TIME_STEP;
+use crate::stage_2_gameplay::resources::{GameTextures, TimeScale};
+
+use super::components::SplitShot;
+use super::minion::spawn_enemy_laser;
+
+// Angles are in degrees, 0 fires straight down and patterns wrapping another one change where it's
+// fired
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub enum BulletPattern {
+    // one bullet
+    #[default]
+    Single,
+    // bullets spread evenly around the full circle
+    Radial(u32),
+    // bullets spread evenly over an arc
+    Fan {
+        count: u32,
+        spread: f32,
+    },
+    // turns the wrapped pattern towards the player
+    Aimed(Box<BulletPattern>),
+    // turns the wrapped pattern a bit further every volley
+    Spiral {
+        pattern: Box<BulletPattern>,
+        step: f32,
+    },
+    // sweeps the wrapped pattern back and forth, one sweep takes period volleys
+    Wave {
+        pattern: Box<BulletPattern>,
+        amplitude: f32,
+        period: u32,
+    },
+    // bullets of the wrapped pattern burst into another pattern after delay seconds
+    Split {
+        pattern: Box<BulletPattern>,
+        delay: f32,
+        into: Box<BulletPattern>,
+    },
+    // several patterns fired at once
+    Volley(Vec<BulletPattern>),
+}
+
+// What every bullet of a pattern is like
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct ShotStats {
+    pub speed: f32,
+    pub damage: f32,
+    pub clash: ProjectileClash,
+}
+
+// Where a pattern is fired from and at
+pub struct PatternContext {
+    pub origin: Vec2,
+    pub target: Option<Vec2>,
+    pub volley: u32, // volleys the shooter fired before this one
+}
+
+pub struct Bullet {
+    pub angle: f32, // in radians, 0 is straight down
+    pub split: Option<(f32, BulletPattern)>,
+}
+
+impl Bullet {
+    fn new(angle: f32) -> Self {
+        Bullet { angle, split: None }
+    }
+
+    // Velocity of the bullet when fired at the given speed
+    pub fn velocity(&self, speed: f32) -> Vec2 {
+        Vec2::new(self.angle.sin(), -self.angle.cos()) * speed
+    }
+}
+
+impl BulletPattern {
+    pub fn bullets(&self, angle: f32, context: &PatternContext) -> Vec<Bullet> {
+        match self {
+            BulletPattern::Single => vec![Bullet::new(angle)],
+            BulletPattern::Radial(count) => (0..*count)
+                .map(|i| Bullet::new(angle + 2. * PI * i as f32 / *count as f32))
+                .collect(),
+            BulletPattern::Fan { count, spread } => {
+                if *count <= 1 {
+                    return vec![Bullet::new(angle)];
+                }
+                let spread = spread.to_radians();
+                (0..*count)
+                    .map(|i| {
+                        Bullet::new(angle - spread / 2. + spread * i as f32 / (*count - 1) as f32)
+                    })
+                    .collect()
+            }
+            BulletPattern::Aimed(pattern) => {
+                let angle = context
+                    .target
+                    .map_or(angle, |target| aim_angle(context.origin, target));
+                pattern.bullets(angle, context)
+            }
+            BulletPattern::Spiral { pattern, step } => {
+                pattern.bullets(angle + step.to_radians() * context.volley as f32, context)
+            }
+            BulletPattern::Wave {
+                pattern,
+                amplitude,
+                period,
+            } => {
+                let phase = 2. * PI * context.volley as f32 / (*period).max(1) as f32;
+                pattern.bullets(angle + amplitude.to_radians() * phase.sin(), context)
+            }
+            BulletPattern::Split {
+                pattern,
+                delay,
+                into,
+            } => pattern
+                .bullets(angle, context)
+                .into_iter()
+                .map(|bullet| Bullet {
+                    split: Some((*delay, (**into).clone())),
+                    ..bullet
+                })
+                .collect(),
+            BulletPattern::Volley(patterns) => patterns
+                .iter()
+                .flat_map(|pattern| pattern.bullets(angle, context))
+                .collect(),
+        }
+    }
+}
+
+fn aim_angle(origin: Vec2, target: Vec2) -> f32 {
+    let direction = target - origin;
+    direction.x.atan2(-direction.y)
+}
+
+pub fn fire_pattern(
+    commands: &mut Commands,
+    game_textures: &GameTextures,
+    pattern: &BulletPattern,
+    context: &PatternContext,
+    angle: f32,
+    shot: ShotStats,
+) {
+    let translation = context.origin.extend(0.);
+
+    for bullet in pattern.bullets(angle, context) {
+        let laser = spawn_enemy_laser(
+            commands,
+            game_textures,
+            translation,
+            bullet.velocity(shot.speed),
+            shot.damage,
+            shot.clash,
+        );
+
+        if let Some((delay, pattern)) = bullet.split {
+            commands.entity(laser).insert(SplitShot {
+                delay,
+                pattern,
+                shot,
+            });
+        }
+    }
+}
+
+// Split shots burst into their next pattern, keeping their heading and speed
+pub fn split_shot_system(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    time_scale: Res<TimeScale>,
+    mut ev_despawn: EventWriter<DespawnEntity>,
+    player_query: Query<&Transform, With<Player>>,
+    mut query: Query<(Entity, &Transform, &Velocity, &mut SplitShot), Without<Player>>,
+) {
+    let target = player_query
+        .get_single()
+        .ok()
+        .map(|player_tf| player_tf.translation.truncate());
+
+    for (entity, tf, velocity, mut split) in query.iter_mut() {
+        split.delay -= TIME_STEP * time_scale.0;
+        if split.delay > 0. {
+            continue;
+        }
+
+        let velocity = Vec2::new(velocity.x, velocity.y);
+        let context = PatternContext {
+            origin: tf.translation.truncate(),
+            target,
+            volley: 0,
+        };
+        fire_pattern(
+            &mut commands,
+            &game_textures,
+            &split.pattern,
+            &context,
+            aim_angle(Vec2::ZERO, velocity),
+            split.shot,
+        );
+
+        ev_despawn.send(DespawnEntity { entity });
+    }
+}
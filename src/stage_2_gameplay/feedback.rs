@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::{AppLooplessStateExt, ConditionSet, IntoConditionalSystem};
+use rand::{thread_rng, Rng};
+
+use super::components::{
+    DamageEvent, DamageNumberToSpawn, HitFlash, Knockback, ParticleConfig, ParticlesToSpawn,
+};
+use super::constants::{HIT_DAMAGE, HIT_FLASH_COLOR, SHAKE_MAX_OFFSET};
+use super::enemy::components::Enemy;
+use super::resources::{CameraShake, HitStop};
+use crate::shared::components::MainCamera;
+use crate::shared::resources::{AppState, Settings};
+
+pub struct FeedbackPlugin;
+
+impl Plugin for FeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::Gameplay)
+                .with_system(hit_flash_system)
+                .with_system(camera_shake_system)
+                .with_system(hit_stop_system)
+                .into(),
+        )
+        // knocked back entities hold still during hit stop like everything else that moves
+        .add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::Gameplay)
+                .run_unless_resource_exists::<HitStop>()
+                .with_system(knockback_system)
+                .into(),
+        )
+        .add_system(
+            hit_effects_system
+                .run_in_state(AppState::Gameplay)
+                .after(HIT_DAMAGE),
+        )
+        .add_exit_system(AppState::Gameplay, camera_reset_system);
+    }
+}
+
+// Sparks where every hit landed, enemies also show how much damage they took
+fn hit_effects_system(
+    mut commands: Commands,
+    mut ev_damage: EventReader<DamageEvent>,
+    enemy_query: Query<(), With<Enemy>>,
+) {
+    for ev in ev_damage.iter() {
+        commands.spawn().insert(ParticlesToSpawn {
+            config: ParticleConfig::sparks(),
+            count: 6,
+            translation: ev.contact,
+        });
+
+        if enemy_query.get(ev.target).is_ok() {
+            commands
+                .spawn()
+                .insert(DamageNumberToSpawn(ev.damage, ev.contact));
+        }
+    }
+}
+
+pub fn hit_flash_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut HitFlash, &mut Sprite)>,
+) {
+    for (entity, mut flash, mut sprite) in query.iter_mut() {
+        flash.timer.tick(time.delta());
+
+        // alpha is left alone so the invincibility flicker keeps working
+        let alpha = sprite.color.a();
+        if flash.timer.finished() {
+            sprite.color = flash.color;
+            commands.entity(entity).remove::<HitFlash>();
+        } else {
+            sprite.color = HIT_FLASH_COLOR;
+        }
+        sprite.color.set_a(alpha);
+    }
+}
+
+fn knockback_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Knockback, &mut Transform)>,
+) {
+    for (entity, mut knockback, mut transform) in query.iter_mut() {
+        transform.translation += knockback.0.extend(0.) * time.delta_seconds();
+
+        // loses most of its speed within a fraction of a second
+        knockback.0 *= 1. - (10. * time.delta_seconds()).min(1.);
+        if knockback.0.length() < 5. {
+            commands.entity(entity).remove::<Knockback>();
+        }
+    }
+}
+
+fn camera_shake_system(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut shake: ResMut<CameraShake>,
+    mut query: Query<&mut Transform, With<MainCamera>>,
+) {
+    if let Ok(mut transform) = query.get_single_mut() {
+        if !settings.screen_shake {
+            shake.trauma = 0.;
+        }
+
+        // offset grows with the square of the trauma so small hits stay subtle
+        let strength = shake.trauma * shake.trauma * SHAKE_MAX_OFFSET;
+        let mut rng = thread_rng();
+        transform.translation.x = rng.gen_range(-1. ..=1.) * strength;
+        transform.translation.y = rng.gen_range(-1. ..=1.) * strength;
+
+        shake.trauma = (shake.trauma - time.delta_seconds()).max(0.);
+    }
+}
+
+fn hit_stop_system(mut commands: Commands, time: Res<Time>, hit_stop: Option<ResMut<HitStop>>) {
+    if let Some(mut hit_stop) = hit_stop {
+        hit_stop.0.tick(time.delta());
+        if hit_stop.0.finished() {
+            commands.remove_resource::<HitStop>();
+        }
+    }
+}
+
+fn camera_reset_system(mut query: Query<&mut Transform, With<MainCamera>>) {
+    if let Ok(mut transform) = query.get_single_mut() {
+        transform.translation.x = 0.;
+        transform.translation.y = 0.;
+    }
+}
@@ -6,18 +6,26 @@ use iyes_loopless::prelude::{
 use iyes_loopless::state::NextState;
 
 use super::components::{
-    DespawnEntity, EntityType, Explosion, ExplosionTimer, ExplosionToSpawn, FromEntity,
-    Invincibility, IsHit, IsHittable, Laser, Movable, Player, SpriteSize, Velocity,
+    Animation, CollisionEvent, Damage, DamageEvent, DamageNumber, DamageNumberToSpawn,
+    DespawnEntity, DespawnOnFinish, EntityType, Explosion, ExplosionToSpawn, FromEntity,
+    Invincibility, IsHittable, Laser, Movable, OffscreenPolicy, ParticleConfig, ParticlesToSpawn,
+    Pickup, Piercing, Player, ProjectileClash, SpriteSize, Velocity,
 };
 use super::constants::{
-    BASE_SPEED, ENEMY_LASER_SPRITE, ENEMY_SPRITE, EXPLOSION_LEN, EXPLOSION_SHEET, GAMEPLAY_RESET,
-    HIT_DETECTION, HIT_PROCESSING, PLAYER_LASER_SPRITE, PLAYER_SPRITE, TIME_STEP,
+    BASE_SPEED, ENEMY_LASER_SPRITE, EXPLOSION_ANIMATION, EXPLOSION_SHEET, GAMEPLAY_RESET,
+    HIT_DAMAGE, HIT_DETECTION, HIT_PROCESSING, INVINCIBILITY_ANIMATION, MAX_CONTINUES, MOVEMENT,
+    PLAYER_LASER_SPRITE, PLAYER_SPRITE, TIME_STEP, WEAPON_UPGRADE_SPRITE,
 };
 use super::enemy::components::{Enemy, EnemyCount};
 use super::enemy::formation::FormationMaker;
-use super::resources::{GameTextures, PlayerState};
-use crate::shared::components::{GameRunning, ResetGameplay, SpawnPlayer};
-use crate::shared::general::despawn_system;
+use super::resources::{
+    CameraShake, CollisionLayer, CollisionMatrix, Combo, DifficultyStats, Director, GameRng,
+    GameTextures, GodMode, HitStop, PlayerRespawn, PlayerState, RescuedShip, TimeScale, Wave,
+};
+use crate::shared::components::{
+    ContinueGameplay, GameRunning, LoadGame, ResetGameplay, SpawnPlayer,
+};
+use crate::shared::resources::{Continues, Difficulty, HighScores, UiTextures};
 use crate::shared::{
     constants::*,
     resources::{AppState, WinSize},
@@ -28,6 +36,8 @@ pub struct GeneralPlugin;
 impl Plugin for GeneralPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<DespawnEntity>()
+            .add_event::<CollisionEvent>()
+            .add_event::<DamageEvent>()
             .add_enter_system_set(
                 AppState::Gameplay,
                 SystemSet::new()
@@ -40,21 +50,47 @@ impl Plugin for GeneralPlugin {
             .add_system_set(
                 ConditionSet::new()
                     .run_in_state(AppState::Gameplay)
+                    .run_unless_resource_exists::<HitStop>()
+                    .label(MOVEMENT)
                     .with_system(movable_system)
+                    .into(),
+            )
+            .add_system(
+                offscreen_system
+                    .run_in_state(AppState::Gameplay)
+                    .after(MOVEMENT),
+            )
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(AppState::Gameplay)
                     .with_system(explosion_to_spawn_system)
-                    .with_system(explosion_animation_system)
+                    .with_system(damage_number_to_spawn_system)
+                    .with_system(damage_number_system)
                     .with_system(invincibility_system)
                     .into(),
             )
+            // Collision detection, contacts are turned into damage for the hit processing systems
             .add_system(
                 laser_hit_system
                     .run_in_state(AppState::Gameplay)
                     .label(HIT_DETECTION),
             )
             .add_system(
-                entity_despawn_system
+                body_collision_system
                     .run_in_state(AppState::Gameplay)
-                    .after(HIT_PROCESSING),
+                    .label(HIT_DETECTION),
+            )
+            .add_system(
+                collision_damage_system
+                    .run_in_state(AppState::Gameplay)
+                    .label(HIT_DAMAGE)
+                    .after(HIT_DETECTION),
+            )
+            // also runs outside of gameplay, a run torn down from the pause menu despawns through it
+            .add_system(
+                entity_despawn_system
+                    .after(HIT_PROCESSING)
+                    .after(GAMEPLAY_RESET),
             )
             // --- Despawns the mobs and resets resources ---
             .add_system_set(
@@ -63,9 +99,11 @@ impl Plugin for GeneralPlugin {
                     .run_if_resource_exists::<ResetGameplay>()
                     .label(GAMEPLAY_RESET)
                     // Despawns everyone on the board
-                    .with_system(despawn_system::<Enemy>)
-                    .with_system(despawn_system::<Player>)
-                    .with_system(despawn_system::<Laser>)
+                    .with_system(despawn_request_system::<Enemy>)
+                    .with_system(despawn_request_system::<Player>)
+                    .with_system(despawn_request_system::<Laser>)
+                    .with_system(despawn_request_system::<Pickup>)
+                    .with_system(despawn_request_system::<DamageNumber>)
                     // Reinitiates resources
                     .with_system(init_game_resource_system)
                     .into(),
@@ -74,6 +112,29 @@ impl Plugin for GeneralPlugin {
                 remove_resource::<ResetGameplay>
                     .run_if_resource_exists::<ResetGameplay>()
                     .after(GAMEPLAY_RESET),
+            )
+            // --- Clears the board and resumes at the start of the current wave ---
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(AppState::Gameplay)
+                    .run_if_resource_exists::<ContinueGameplay>()
+                    .label(GAMEPLAY_RESET)
+                    .with_system(despawn_request_system::<Enemy>)
+                    .with_system(despawn_request_system::<Laser>)
+                    .with_system(despawn_request_system::<Pickup>)
+                    .with_system(despawn_request_system::<DamageNumber>)
+                    .with_system(continue_run_system)
+                    .into(),
+            )
+            // a run is over once the game over screen is left without continuing
+            .add_exit_system(
+                AppState::GameOver,
+                record_high_score_system.run_unless_resource_exists::<ContinueGameplay>(),
+            )
+            .add_system(
+                remove_resource::<ContinueGameplay>
+                    .run_if_resource_exists::<ContinueGameplay>()
+                    .after(GAMEPLAY_RESET),
             );
     }
 }
@@ -83,6 +144,7 @@ fn game_setup_system(
     asset_server: Res<AssetServer>,
     mut windows: ResMut<Windows>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    load_game: Option<Res<LoadGame>>,
 ) {
     // capture window size
     let window = windows.get_primary_mut().unwrap();
@@ -101,102 +163,263 @@ fn game_setup_system(
     let game_textures = GameTextures {
         player: asset_server.load(PLAYER_SPRITE),
         player_laser: asset_server.load(PLAYER_LASER_SPRITE),
-        enemy: asset_server.load(ENEMY_SPRITE),
+        weapon_upgrade: asset_server.load(WEAPON_UPGRADE_SPRITE),
         enemy_laser: asset_server.load(ENEMY_LASER_SPRITE),
         explosion,
     };
 
     commands.insert_resource(game_textures);
-    commands.insert_resource(SpawnPlayer);
+    commands.insert_resource(CollisionMatrix::load());
+
+    // a resumed run places the player itself
+    if load_game.is_none() {
+        commands.insert_resource(SpawnPlayer);
+    }
 }
 
-fn init_game_resource_system(mut commands: Commands) {
+fn init_game_resource_system(mut commands: Commands, difficulty: Res<Difficulty>) {
     commands.insert_resource(EnemyCount::default());
-    commands.insert_resource(PlayerState::default());
+    commands.insert_resource(PlayerState::new(
+        DifficultyStats::from(*difficulty).max_health,
+    ));
+    commands.insert_resource(CameraShake::default());
+    commands.insert_resource(Wave::default());
+    commands.insert_resource(GameRng::default());
+    commands.insert_resource(Director::default());
+    commands.insert_resource(Combo::default());
+    commands.insert_resource(TimeScale::default());
+    commands.remove_resource::<HitStop>();
+    commands.remove_resource::<PlayerRespawn>();
+    commands.remove_resource::<GodMode>();
+    commands.remove_resource::<RescuedShip>();
+    commands.insert_resource(Continues(MAX_CONTINUES));
     commands.insert_resource(GameRunning);
 }
 
-fn movable_system(
+fn record_high_score_system(
+    player_state: Res<PlayerState>,
+    difficulty: Res<Difficulty>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    if player_state.score > 0 {
+        high_scores.record(player_state.score, *difficulty);
+        high_scores.save();
+    }
+}
+
+fn continue_run_system(
     mut commands: Commands,
+    mut player_state: ResMut<PlayerState>,
+    mut wave: ResMut<Wave>,
+    mut continues: ResMut<Continues>,
+) {
+    player_state.continue_run();
+    wave.restart();
+    continues.0 = continues.0.saturating_sub(1);
+
+    commands.insert_resource(Combo::default());
+    commands.remove_resource::<HitStop>();
+    commands.remove_resource::<PlayerRespawn>();
+}
+
+fn movable_system(
+    time_scale: Res<TimeScale>,
+    mut query: Query<(&Velocity, &mut Transform), With<Movable>>,
+) {
+    let time_step = TIME_STEP * time_scale.0;
+
+    for (velocity, mut transform) in query.iter_mut() {
+        let translation = &mut transform.translation;
+        translation.x += velocity.x * time_step * BASE_SPEED;
+        translation.y += velocity.y * time_step * BASE_SPEED;
+    }
+}
+
+fn offscreen_system(
     win_size: Res<WinSize>,
-    mut query: Query<(Entity, &Velocity, &mut Transform, &Movable)>,
+    mut ev_despawn: EventWriter<DespawnEntity>,
+    mut query: Query<(
+        Entity,
+        &OffscreenPolicy,
+        &mut Transform,
+        Option<&SpriteSize>,
+        Option<&mut Velocity>,
+    )>,
 ) {
-    for (entity, velocity, mut transform, movable) in query.iter_mut() {
+    let (max_x, max_y) = (win_size.w / 2., win_size.h / 2.);
+
+    for (entity, policy, mut transform, size, velocity) in query.iter_mut() {
+        let half_size = size.map_or(Vec2::ZERO, |size| size.0 * transform.scale.xy() / 2.);
         let translation = &mut transform.translation;
-        translation.x += velocity.x * TIME_STEP * BASE_SPEED;
-        translation.y += velocity.y * TIME_STEP * BASE_SPEED;
-
-        if movable.auto_despawn {
-            const MARGIN: f32 = 200.;
-            if translation.y > win_size.h / 2. + MARGIN
-                || translation.y < -win_size.h / 2. - MARGIN
-                || translation.x > win_size.w / 2. + MARGIN
-                || translation.x < -win_size.w / 2. - MARGIN
-            {
-                commands.entity(entity).despawn();
+
+        match policy {
+            OffscreenPolicy::Despawn(margin) => {
+                if translation.x.abs() > max_x + margin || translation.y.abs() > max_y + margin {
+                    ev_despawn.send(DespawnEntity { entity });
+                }
+            }
+            // only wraps once the sprite is completely out of view
+            OffscreenPolicy::Wrap => {
+                let (wrap_x, wrap_y) = (max_x + half_size.x, max_y + half_size.y);
+                if translation.x.abs() > wrap_x {
+                    translation.x = -translation.x.signum() * wrap_x;
+                }
+                if translation.y.abs() > wrap_y {
+                    translation.y = -translation.y.signum() * wrap_y;
+                }
+            }
+            // the whole sprite stays in view
+            OffscreenPolicy::Bounce | OffscreenPolicy::Clamp => {
+                let limit_x = (max_x - half_size.x).max(0.);
+                let limit_y = (max_y - half_size.y).max(0.);
+                let (out_x, out_y) = (translation.x.abs() > limit_x, translation.y.abs() > limit_y);
+                translation.x = translation.x.clamp(-limit_x, limit_x);
+                translation.y = translation.y.clamp(-limit_y, limit_y);
+
+                if let (OffscreenPolicy::Bounce, Some(mut velocity)) = (policy, velocity) {
+                    if out_x {
+                        velocity.x = -translation.x.signum() * velocity.x.abs();
+                    }
+                    if out_y {
+                        velocity.y = -translation.y.signum() * velocity.y.abs();
+                    }
+                }
             }
         }
     }
 }
 
+// Requests a despawn for everything with the component
+pub fn despawn_request_system<T: Component>(
+    mut ev_despawn: EventWriter<DespawnEntity>,
+    query: Query<Entity, With<T>>,
+) {
+    for entity in query.iter() {
+        ev_despawn.send(DespawnEntity { entity });
+    }
+}
+
 fn entity_despawn_system(
     mut commands: Commands,
     mut ev_despawn: EventReader<DespawnEntity>,
-    mut enemy_count: ResMut<EnemyCount>,
+    mut enemy_count: Option<ResMut<EnemyCount>>,
+    added_query: Query<&EntityType, Added<Enemy>>,
+    query: Query<(Option<&EntityType>, Option<&Enemy>)>,
 ) {
+    // enemies are counted here too, so none can leave before it was counted
+    if let Some(enemy_count) = enemy_count.as_mut() {
+        for entity_type in added_query.iter() {
+            enemy_count.add(entity_type);
+        }
+    }
+
+    // the same entity can be requested more than once, ie. killed while leaving the screen,
+    // and might already be gone after a reset or teardown
+    let mut despawned = HashSet::default();
     for ev in ev_despawn.iter() {
-        commands.entity(ev.entity).despawn();
+        let (entity_type, enemy) = match query.get(ev.entity) {
+            Ok(entity) => entity,
+            Err(_) => continue,
+        };
+        if !despawned.insert(ev.entity) {
+            continue;
+        }
 
-        match ev.entity_type {
-            EntityType::Asteroid => {
-                enemy_count.asteroids -= 1;
-            }
-            EntityType::Minion => {
-                enemy_count.minions -= 1;
+        if let Some(entity_type) = entity_type {
+            despawn_cleanup(entity_type, enemy.is_some(), enemy_count.as_deref_mut());
+        }
+        commands.entity(ev.entity).despawn_recursive();
+    }
+}
+
+// Cleanup hooks for each type of entity, run however it leaves the board
+fn despawn_cleanup(entity_type: &EntityType, enemy: bool, enemy_count: Option<&mut EnemyCount>) {
+    match entity_type {
+        EntityType::Asteroid | EntityType::Minion => {
+            if let Some(enemy_count) = enemy_count.filter(|_| enemy) {
+                enemy_count.remove(entity_type);
             }
-            _ => {}
         }
+        // lives and respawns are handled by whoever destroyed the ship
+        EntityType::Player => {}
     }
 }
 
 fn laser_hit_system(
     mut commands: Commands,
-    laser_query: Query<(Entity, &Transform, &SpriteSize, &FromEntity), With<Laser>>,
+    matrix: Res<CollisionMatrix>,
+    mut ev_collision: EventWriter<CollisionEvent>,
+    mut ev_despawn: EventWriter<DespawnEntity>,
+    mut laser_query: Query<
+        (
+            Entity,
+            &Transform,
+            &SpriteSize,
+            &FromEntity,
+            Option<&mut Piercing>,
+            Option<&ProjectileClash>,
+        ),
+        With<Laser>,
+    >,
     entity_query: Query<
         (Entity, &Transform, &SpriteSize, &EntityType),
         (With<IsHittable>, Without<Invincibility>),
     >,
 ) {
-    let mut processed_entities: HashSet<Entity> = HashSet::new();
+    let mut processed_lasers: HashSet<Entity> = HashSet::new();
 
-    for (entity, entity_tf, entity_size, entity_type) in entity_query.iter() {
-        if processed_entities.contains(&entity) {
-            continue;
-        }
+    // player and enemy projectiles that run into each other are dealt with first
+    let clashing: Vec<_> = laser_query
+        .iter()
+        .filter_map(|(entity, tf, size, from_entity, _, clash)| {
+            let from_player = matches!(from_entity, FromEntity::FromPlayer);
+            clash.map(|clash| (entity, tf, size.0 * tf.scale.xy(), from_player, *clash))
+        })
+        .collect();
 
-        let entity_scale = entity_tf.scale.xy();
+    for (i, &(entity_a, tf_a, size_a, from_player_a, clash_a)) in clashing.iter().enumerate() {
+        for &(entity_b, tf_b, size_b, from_player_b, clash_b) in clashing[i + 1..].iter() {
+            let destroyed_a = clash_b.cancels && clash_a.destructible;
+            let destroyed_b = clash_a.cancels && clash_b.destructible;
+            if from_player_a == from_player_b
+                || !(destroyed_a || destroyed_b)
+                || processed_lasers.contains(&entity_a)
+                || processed_lasers.contains(&entity_b)
+                || collide(tf_a.translation, size_a, tf_b.translation, size_b).is_none()
+            {
+                continue;
+            }
 
-        for (laser_entity, laser_tf, laser_size, from_entity) in laser_query.iter() {
-            // if entity is player and it's a player laser then skip
-            // same if enemy entity and enemy laser
-            match entity_type {
-                EntityType::Player => {
-                    if let FromEntity::FromPlayer = from_entity {
-                        continue;
-                    }
-                }
-                _ => {
-                    if let FromEntity::FromEnemy = from_entity {
-                        continue;
-                    }
+            for (entity, destroyed) in [(entity_a, destroyed_a), (entity_b, destroyed_b)] {
+                if destroyed {
+                    ev_despawn.send(DespawnEntity { entity });
+                    processed_lasers.insert(entity);
                 }
-            };
+            }
+
+            commands.spawn().insert(ParticlesToSpawn {
+                config: ParticleConfig::sparks(),
+                count: 4,
+                translation: tf_a.translation,
+            });
+        }
+    }
 
-            if processed_entities.contains(&entity) || processed_entities.contains(&laser_entity) {
+    for (laser_entity, laser_tf, laser_size, from_entity, mut piercing, _) in laser_query.iter_mut()
+    {
+        if processed_lasers.contains(&laser_entity) {
+            continue;
+        }
+
+        let laser_layer = CollisionLayer::of_laser(from_entity);
+        let laser_scale = laser_tf.scale.xy();
+
+        for (entity, entity_tf, entity_size, entity_type) in entity_query.iter() {
+            if !matrix.interacts(laser_layer, CollisionLayer::of_entity(entity_type)) {
                 continue;
             }
 
-            let laser_scale = laser_tf.scale.xy();
+            let entity_scale = entity_tf.scale.xy();
 
             // determine if the collision has happened
             let collision = collide(
@@ -205,23 +428,112 @@ fn laser_hit_system(
                 entity_tf.translation,
                 entity_size.0 * entity_scale,
             );
+            if collision.is_none() {
+                continue;
+            }
 
-            // laser has collided with the entity
-            if collision.is_some() {
-                // remove laser
-                commands.entity(laser_entity).despawn();
-                processed_entities.insert(laser_entity);
+            // piercing lasers keep going but hit every entity only once
+            if let Some(piercing) = piercing.as_mut() {
+                if !piercing.0.insert(entity) {
+                    continue;
+                }
+            }
 
-                // Add hit to entity so that another system processes it
-                commands.entity(entity).insert(IsHit);
-                processed_entities.insert(entity);
+            ev_collision.send(CollisionEvent {
+                entities: [laser_entity, entity],
+                contact: laser_tf.translation,
+            });
 
+            // remove laser
+            if piercing.is_none() {
+                ev_despawn.send(DespawnEntity {
+                    entity: laser_entity,
+                });
                 break;
             }
         }
     }
 }
 
+// Ships and asteroids running into each other
+fn body_collision_system(
+    matrix: Res<CollisionMatrix>,
+    mut ev_collision: EventWriter<CollisionEvent>,
+    query: Query<
+        (Entity, &Transform, &SpriteSize, &EntityType),
+        (With<IsHittable>, Without<Invincibility>),
+    >,
+) {
+    for [a, b] in query.iter_combinations() {
+        let (entity_a, tf_a, size_a, type_a) = a;
+        let (entity_b, tf_b, size_b, type_b) = b;
+
+        let (layer_a, layer_b) = (
+            CollisionLayer::of_entity(type_a),
+            CollisionLayer::of_entity(type_b),
+        );
+        if !matrix.interacts(layer_a, layer_b) {
+            continue;
+        }
+
+        // determine if the collision has happened
+        let collision = collide(
+            tf_a.translation,
+            size_a.0 * tf_a.scale.xy(),
+            tf_b.translation,
+            size_b.0 * tf_b.scale.xy(),
+        );
+
+        if collision.is_some() {
+            ev_collision.send(CollisionEvent {
+                entities: [entity_a, entity_b],
+                contact: (tf_a.translation + tf_b.translation) / 2.,
+            });
+        }
+    }
+}
+
+// Turns contacts into damage, the collision matrix decides who gets hurt and how much
+fn collision_damage_system(
+    matrix: Res<CollisionMatrix>,
+    mut ev_collision: EventReader<CollisionEvent>,
+    mut ev_damage: EventWriter<DamageEvent>,
+    query: Query<(Option<&EntityType>, Option<&FromEntity>, Option<&Damage>)>,
+) {
+    // lasers belong to whoever fired them, everything else goes by its entity type
+    let layer = |entity: Entity| match query.get(entity) {
+        Ok((_, Some(from_entity), _)) => Some(CollisionLayer::of_laser(from_entity)),
+        Ok((Some(entity_type), None, _)) => Some(CollisionLayer::of_entity(entity_type)),
+        _ => None,
+    };
+
+    for ev in ev_collision.iter() {
+        let [a, b] = ev.entities;
+        let (layer_a, layer_b) = match (layer(a), layer(b)) {
+            (Some(layer_a), Some(layer_b)) => (layer_a, layer_b),
+            _ => continue,
+        };
+
+        for (source, source_layer, target, target_layer) in
+            [(a, layer_a, b, layer_b), (b, layer_b, a, layer_a)]
+        {
+            if let Some(multiplier) = matrix.damage(source_layer, target_layer) {
+                // things without a damage of their own still hurt a bit
+                let damage = match query.get(source) {
+                    Ok((_, _, Some(damage))) => damage.0,
+                    _ => 1.,
+                };
+                ev_damage.send(DamageEvent {
+                    source,
+                    target,
+                    contact: ev.contact,
+                    damage: damage * multiplier,
+                });
+            }
+        }
+    }
+}
+
 fn explosion_to_spawn_system(
     mut commands: Commands,
     game_textures: Res<GameTextures>,
@@ -239,25 +551,67 @@ fn explosion_to_spawn_system(
                 ..Default::default()
             })
             .insert(Explosion)
-            .insert(ExplosionTimer::default());
+            .insert(Animation::new(EXPLOSION_ANIMATION))
+            .insert(DespawnOnFinish);
 
         // despawn the explosionToSpawn
         commands.entity(explosion_spawn_entity).despawn();
     }
 }
 
-fn explosion_animation_system(
+fn damage_number_to_spawn_system(
+    mut commands: Commands,
+    ui_textures: Res<UiTextures>,
+    query: Query<(Entity, &DamageNumberToSpawn)>,
+) {
+    for (damage_spawn_entity, DamageNumberToSpawn(damage, translation)) in query.iter() {
+        // whole numbers are shown without the decimals
+        let value = if damage.fract() == 0. {
+            format!("{}", damage)
+        } else {
+            format!("{:.1}", damage)
+        };
+
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::with_section(
+                    value,
+                    TextStyle {
+                        font: ui_textures.ui_font.clone(),
+                        font_size: 24.0,
+                        color: Color::rgb(1., 0.9, 0.3),
+                    },
+                    TextAlignment {
+                        vertical: VerticalAlign::Center,
+                        horizontal: HorizontalAlign::Center,
+                    },
+                ),
+                transform: Transform::from_xyz(translation.x, translation.y, 20.),
+                ..Default::default()
+            })
+            .insert(DamageNumber::default());
+
+        commands.entity(damage_spawn_entity).despawn();
+    }
+}
+
+fn damage_number_system(
     mut commands: Commands,
     time: Res<Time>,
-    mut query: Query<(Entity, &mut ExplosionTimer, &mut TextureAtlasSprite), With<Explosion>>,
+    mut query: Query<(Entity, &mut DamageNumber, &mut Transform, &mut Text)>,
 ) {
-    for (entity, mut timer, mut sprite) in query.iter_mut() {
-        timer.0.tick(time.delta());
-        if timer.0.finished() {
-            sprite.index += 1;
-            if sprite.index >= EXPLOSION_LEN {
-                commands.entity(entity).despawn();
-            }
+    for (entity, mut damage_number, mut transform, mut text) in query.iter_mut() {
+        damage_number.0.tick(time.delta());
+
+        // numbers float upwards and fade out until the timer runs out
+        transform.translation.y += 60. * time.delta_seconds();
+        let alpha = 1. - damage_number.0.percent();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(alpha);
+        }
+
+        if damage_number.0.finished() {
+            commands.entity(entity).despawn();
         }
     }
 }
@@ -265,24 +619,25 @@ fn explosion_animation_system(
 fn invincibility_system(
     mut commands: Commands,
     time: Res<Time>,
+    added_query: Query<Entity, Added<Invincibility>>,
     mut query: Query<(Entity, &mut Invincibility, &mut Sprite)>,
 ) {
+    // invincible entities flicker until it runs out
+    for entity in added_query.iter() {
+        commands
+            .entity(entity)
+            .insert(Animation::new(INVINCIBILITY_ANIMATION));
+    }
+
     for (entity, mut invincibility, mut sprite) in query.iter_mut() {
         invincibility.length -= time.delta().as_secs_f32();
-        invincibility.animation_timer.tick(time.delta());
-
-        // entities alpha color is reset so it seems like the player is flickering
-        if invincibility.animation_timer.finished() {
-            let color_a = sprite.color.a();
-            match sprite.color.a() {
-                a if a == 1. => sprite.color.set_a(0.3),
-                _ => sprite.color.set_a(1.),
-            };
-        }
 
         // when invincibility runs out then remove invincibility component and reset alpha for color
         if invincibility.length < 0. {
-            commands.entity(entity).remove::<Invincibility>();
+            commands
+                .entity(entity)
+                .remove::<Invincibility>()
+                .remove::<Animation>();
             sprite.color.set_a(1.);
         }
     }
@@ -291,3 +646,170 @@ fn invincibility_system(
 fn remove_resource<R: Resource>(mut commands: Commands) {
     commands.remove_resource::<R>();
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{despawn_request_system, entity_despawn_system, movable_system, offscreen_system};
+    use crate::shared::components::GameplayTeardown;
+    use crate::stage_2_gameplay::components::{
+        DespawnEntity, EntityType, Movable, OffscreenPolicy, SpriteSize, Velocity,
+    };
+    use crate::stage_2_gameplay::enemy::components::{Enemy, EnemyCount, EnemyMovement};
+    use crate::stage_2_gameplay::enemy::motion::enemy_movement_system;
+    use crate::stage_2_gameplay::testing::TestWorld;
+    use crate::stage_2_gameplay::ui::teardown_system;
+
+    // Just the parts of the gameplay loop that move and despawn entities
+    fn game() -> TestWorld {
+        let mut game = TestWorld::new();
+        game.add_system(movable_system)
+            .add_system(enemy_movement_system)
+            .add_system(offscreen_system)
+            .add_system(entity_despawn_system);
+        game
+    }
+
+    fn spawn_enemy(game: &mut TestWorld, entity_type: EntityType, x: f32) -> Entity {
+        game.world
+            .spawn()
+            .insert(Enemy)
+            .insert(entity_type)
+            .insert(EnemyMovement::default())
+            .insert(OffscreenPolicy::default())
+            .insert(Transform::from_xyz(x, 0., 0.))
+            .id()
+    }
+
+    fn spawn_movable(
+        game: &mut TestWorld,
+        x: f32,
+        velocity: f32,
+        policy: OffscreenPolicy,
+    ) -> Entity {
+        game.world
+            .spawn()
+            .insert(Velocity { x: velocity, y: 0. })
+            .insert(Movable)
+            .insert(policy)
+            .insert(SpriteSize(Vec2::splat(20.)))
+            .insert(Transform::from_xyz(x, 0., 0.))
+            .id()
+    }
+
+    fn x(game: &TestWorld, entity: Entity) -> f32 {
+        game.world.get::<Transform>(entity).unwrap().translation.x
+    }
+
+    fn enemy_count(game: &TestWorld) -> &EnemyCount {
+        game.world.resource::<EnemyCount>()
+    }
+
+    fn assert_consistent(game: &mut TestWorld) {
+        let mut query = game.world.query_filtered::<&EntityType, With<Enemy>>();
+        let mut real = EnemyCount::default();
+        for entity_type in query.iter(&game.world) {
+            real.add(entity_type);
+        }
+        assert_eq!(enemy_count(game), &real);
+    }
+
+    #[test]
+    fn counts_follow_spawns_and_despawn_requests() {
+        let mut game = game();
+        let asteroid = spawn_enemy(&mut game, EntityType::Asteroid, 0.);
+        spawn_enemy(&mut game, EntityType::Asteroid, 50.);
+        spawn_enemy(&mut game, EntityType::Minion, -50.);
+        game.frame();
+        assert_eq!(
+            enemy_count(&game),
+            &EnemyCount {
+                asteroids: 2,
+                minions: 1
+            }
+        );
+
+        // killed twice in the same frame still only counts once
+        game.send(DespawnEntity { entity: asteroid });
+        game.send(DespawnEntity { entity: asteroid });
+        game.frame();
+        assert_eq!(
+            enemy_count(&game),
+            &EnemyCount {
+                asteroids: 1,
+                minions: 1
+            }
+        );
+        assert_consistent(&mut game);
+    }
+
+    #[test]
+    fn off_screen_exits_go_through_despawn_requests() {
+        let mut game = game();
+        spawn_enemy(&mut game, EntityType::Minion, 0.);
+        let gone = spawn_enemy(&mut game, EntityType::Minion, 1000.);
+        let laser = spawn_movable(&mut game, -1000., 0., OffscreenPolicy::default());
+        game.frame();
+
+        assert!(game.world.get_entity(gone).is_none());
+        assert!(game.world.get_entity(laser).is_none());
+        assert_eq!(
+            enemy_count(&game),
+            &EnemyCount {
+                asteroids: 0,
+                minions: 1
+            }
+        );
+        assert_consistent(&mut game);
+    }
+
+    #[test]
+    fn offscreen_policies_keep_entities_around() {
+        let mut game = game();
+        let wrap = spawn_movable(&mut game, 315., 1., OffscreenPolicy::Wrap);
+        let bounce = spawn_movable(&mut game, 295., 1., OffscreenPolicy::Bounce);
+        let clamp = spawn_movable(&mut game, 295., 1., OffscreenPolicy::Clamp);
+        game.frame();
+
+        // screen is 600 wide and the sprites are 20 wide
+        assert_eq!(x(&game, wrap), -310.);
+        assert_eq!(x(&game, bounce), 290.);
+        assert!(game.world.get::<Velocity>(bounce).unwrap().x < 0.);
+        assert_eq!(x(&game, clamp), 290.);
+        assert!(game.world.get::<Velocity>(clamp).unwrap().x > 0.);
+    }
+
+    #[test]
+    fn reset_clears_counts() {
+        let mut game = game();
+        let asteroid = spawn_enemy(&mut game, EntityType::Asteroid, 0.);
+        spawn_enemy(&mut game, EntityType::Minion, 0.);
+        game.frame();
+
+        // request still pending when the reset removes every enemy
+        game.send(DespawnEntity { entity: asteroid });
+        game.run(despawn_request_system::<Enemy>);
+        game.frame();
+
+        assert_eq!(enemy_count(&game), &EnemyCount::default());
+        assert_consistent(&mut game);
+    }
+
+    #[test]
+    fn teardown_clears_counts() {
+        let mut game = game();
+        spawn_enemy(&mut game, EntityType::Asteroid, 0.);
+        let minion = spawn_enemy(&mut game, EntityType::Minion, 0.);
+        spawn_movable(&mut game, 0., 0., OffscreenPolicy::default());
+        game.frame();
+
+        game.send(DespawnEntity { entity: minion });
+        game.world.insert_resource(GameplayTeardown);
+        game.run(teardown_system::<GameplayTeardown>);
+        game.frame();
+
+        assert_eq!(enemy_count(&game), &EnemyCount::default());
+        assert_consistent(&mut game);
+    }
+}
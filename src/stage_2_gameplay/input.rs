@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::IntoConditionalSystem;
+
+use super::components::Player;
+use super::console::Console;
+use super::constants::{BASE_SPEED, PLAYER_INPUT, POINTER_MAX_SPEED, TIME_STEP};
+use super::resources::PlayerInput;
+use crate::shared::resources::{AppState, ControlScheme, FireMode, Settings};
+
+pub struct PlayerInputPlugin;
+
+impl Plugin for PlayerInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerInput>().add_system(
+            player_input_system
+                .run_in_state(AppState::Gameplay)
+                .label(PLAYER_INPUT),
+        );
+    }
+}
+
+fn player_input_system(
+    kb: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    settings: Res<Settings>,
+    console: Option<Res<Console>>,
+    mut input: ResMut<PlayerInput>,
+    query: Query<&Transform, With<Player>>,
+) {
+    // player controls are ignored while the console is open
+    if console.is_some() {
+        *input = PlayerInput::default();
+        return;
+    }
+
+    *input = match settings.controls {
+        ControlScheme::Keyboard => keyboard_input(&kb, settings.fire_mode),
+        ControlScheme::Pointer => pointer_input(&mouse, &windows, query.get_single().ok()),
+    };
+}
+
+fn keyboard_input(kb: &Input<KeyCode>, fire_mode: FireMode) -> PlayerInput {
+    let movement = if kb.pressed(KeyCode::Left) || kb.pressed(KeyCode::A) {
+        -1.
+    } else if kb.pressed(KeyCode::Right) || kb.pressed(KeyCode::D) {
+        1.
+    } else {
+        0.
+    };
+
+    let held = kb.pressed(KeyCode::Space);
+    PlayerInput {
+        movement,
+        fire: match fire_mode {
+            FireMode::Tap => kb.just_pressed(KeyCode::Space),
+            FireMode::Hold => held,
+            FireMode::Auto => true,
+        },
+        charge: held && fire_mode != FireMode::Hold,
+    }
+}
+
+// Ship heads for the cursor as fast as it's allowed to, slowing down so it doesn't overshoot
+//
+// Fires while the left button is held whatever the fire mode, the right button charges
+fn pointer_input(
+    mouse: &Input<MouseButton>,
+    windows: &Windows,
+    player: Option<&Transform>,
+) -> PlayerInput {
+    let cursor_x = windows.get_primary().and_then(|window| {
+        window
+            .cursor_position()
+            .map(|position| position.x - window.width() / 2.)
+    });
+
+    let movement = match (cursor_x, player) {
+        (Some(cursor_x), Some(player_tf)) => {
+            let distance = cursor_x - player_tf.translation.x;
+            (distance / (BASE_SPEED * TIME_STEP)).clamp(-POINTER_MAX_SPEED, POINTER_MAX_SPEED)
+        }
+        _ => 0.,
+    };
+
+    PlayerInput {
+        movement,
+        fire: mouse.pressed(MouseButton::Left),
+        charge: mouse.pressed(MouseButton::Right),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+
+    use super::player_input_system;
+    use crate::shared::resources::{FireMode, Settings};
+    use crate::stage_2_gameplay::components::{Laser, Player, Weapon};
+    use crate::stage_2_gameplay::player::firing_cooldown_system;
+    use crate::stage_2_gameplay::resources::GameTextures;
+    use crate::stage_2_gameplay::testing::TestWorld;
+    use crate::stage_2_gameplay::weapon::player_fire_system;
+
+    #[test]
+    fn holding_fire_keeps_firing_through_cooldowns() {
+        let mut game = TestWorld::new();
+        game.add_system(player_input_system)
+            .add_system(player_fire_system)
+            .add_system(firing_cooldown_system);
+
+        let mut kb = Input::<KeyCode>::default();
+        kb.press(KeyCode::Space);
+        game.world.insert_resource(kb);
+        game.world.insert_resource(Input::<MouseButton>::default());
+        game.world.insert_resource(Windows::default());
+        game.world.insert_resource(Settings {
+            fire_mode: FireMode::Hold,
+            ..Default::default()
+        });
+        game.world.insert_resource(GameTextures {
+            player: Default::default(),
+            player_laser: Default::default(),
+            weapon_upgrade: Default::default(),
+            enemy_laser: Default::default(),
+            explosion: Default::default(),
+        });
+        game.world
+            .spawn()
+            .insert(Player)
+            .insert(Weapon::default())
+            .insert(Transform::default());
+
+        let lasers = |game: &mut TestWorld| {
+            game.world
+                .query_filtered::<(), With<Laser>>()
+                .iter(&game.world)
+                .count()
+        };
+
+        game.world.resource_mut::<Time>().update();
+        game.frame();
+        let volley = lasers(&mut game);
+        assert!(volley > 0);
+
+        // held long enough to charge several times over, the twin laser cools down in half a second
+        for _ in 0..32 {
+            thread::sleep(Duration::from_millis(50));
+            game.world.resource_mut::<Time>().update();
+            game.frame();
+        }
+        assert!(lasers(&mut game) >= 3 * volley);
+    }
+}
@@ -1,17 +1,43 @@
 use bevy::prelude::*;
 
+use animation::AnimationPlugin;
+use background::BackgroundPlugin;
+use combo::ComboPlugin;
+use console::ConsolePlugin;
+use debug::DebugPlugin;
+use director::DirectorPlugin;
 use enemy::EnemyPlugin;
+use feedback::FeedbackPlugin;
 use general::GeneralPlugin;
+use input::PlayerInputPlugin;
+use particles::ParticlePlugin;
+use pickup::PickupPlugin;
 use player::PlayerPlugin;
+use save::SavePlugin;
 use ui::UiPlugin;
+use weapon::WeaponPlugin;
 
+mod animation;
+mod background;
+mod combo;
 mod components;
+mod console;
 mod constants;
+mod debug;
+mod director;
 mod enemy;
+mod feedback;
 mod general;
+mod input;
+mod particles;
+mod pickup;
 mod player;
 mod resources;
+mod save;
+#[cfg(test)]
+mod testing;
 mod ui;
+mod weapon;
 
 pub struct GameplayStage;
 
@@ -19,7 +45,19 @@ impl Plugin for GameplayStage {
     fn build(&self, app: &mut App) {
         app.add_plugin(GeneralPlugin)
             .add_plugin(EnemyPlugin)
+            .add_plugin(PlayerInputPlugin)
             .add_plugin(PlayerPlugin)
+            .add_plugin(WeaponPlugin)
+            .add_plugin(PickupPlugin)
+            .add_plugin(FeedbackPlugin)
+            .add_plugin(ParticlePlugin)
+            .add_plugin(BackgroundPlugin)
+            .add_plugin(AnimationPlugin)
+            .add_plugin(SavePlugin)
+            .add_plugin(DirectorPlugin)
+            .add_plugin(ComboPlugin)
+            .add_plugin(DebugPlugin)
+            .add_plugin(ConsolePlugin)
             .add_plugin(UiPlugin);
     }
 }
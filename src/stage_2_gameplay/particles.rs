@@ -0,0 +1,171 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use iyes_loopless::prelude::ConditionSet;
+use rand::{thread_rng, Rng};
+
+use super::components::{Particle, ParticleConfig, ParticleEmitter, ParticlesToSpawn};
+use crate::shared::resources::AppState;
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::Gameplay)
+                .with_system(particle_emitter_system)
+                .with_system(particles_to_spawn_system)
+                .with_system(particle_system)
+                .into(),
+        );
+    }
+}
+
+impl ParticleConfig {
+    pub fn engine_trail() -> Self {
+        ParticleConfig {
+            lifetime: 0.35,
+            direction: PI,
+            spread: 0.25,
+            speed: (80., 140.),
+            area: Vec2::new(6., 2.),
+            size: (5., 1.),
+            color: (Color::rgb(1., 0.8, 0.3), Color::rgba(1., 0.2, 0., 0.)),
+            z: 1.,
+        }
+    }
+
+    pub fn sparks() -> Self {
+        ParticleConfig {
+            lifetime: 0.25,
+            direction: 0.,
+            spread: PI,
+            speed: (150., 350.),
+            area: Vec2::ZERO,
+            size: (3., 1.),
+            color: (Color::rgb(1., 1., 0.7), Color::rgba(1., 0.6, 0.1, 0.)),
+            z: 15.,
+        }
+    }
+
+    pub fn debris() -> Self {
+        ParticleConfig {
+            lifetime: 0.8,
+            direction: 0.,
+            spread: PI,
+            speed: (40., 160.),
+            area: Vec2::new(20., 20.),
+            size: (6., 3.),
+            color: (
+                Color::rgb(0.55, 0.45, 0.35),
+                Color::rgba(0.3, 0.25, 0.2, 0.),
+            ),
+            z: 15.,
+        }
+    }
+}
+
+fn spawn_particle(
+    commands: &mut Commands,
+    config: &ParticleConfig,
+    translation: Vec3,
+    rng: &mut impl Rng,
+) {
+    let angle = config.direction + rng.gen_range(-config.spread..=config.spread);
+    let speed = rng.gen_range(config.speed.0..=config.speed.1);
+    let half_area = config.area / 2.;
+    let x = translation.x + rng.gen_range(-half_area.x..=half_area.x);
+    let y = translation.y + rng.gen_range(-half_area.y..=half_area.y);
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: config.color.0,
+                custom_size: Some(Vec2::splat(config.size.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(x, y, config.z),
+            ..Default::default()
+        })
+        .insert(Particle {
+            velocity: Vec2::new(angle.sin(), angle.cos()) * speed,
+            lifetime: Timer::from_seconds(config.lifetime, false),
+            size: config.size,
+            color: config.color,
+        });
+}
+
+fn particle_emitter_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(&Transform, &mut ParticleEmitter)>,
+) {
+    let mut rng = thread_rng();
+
+    for (transform, mut emitter) in query.iter_mut() {
+        // keeps the leftover fraction so low rates still emit over time
+        emitter.pending += emitter.rate * time.delta_seconds();
+
+        // offset follows the rotation of the entity, ie. trails stay behind the engine
+        let offset = transform.rotation * emitter.offset.extend(0.);
+        let translation = transform.translation + offset;
+
+        while emitter.pending >= 1. {
+            emitter.pending -= 1.;
+            let mut config = emitter.config.clone();
+            config.direction -= transform.rotation.to_euler(EulerRot::XYZ).2;
+            spawn_particle(&mut commands, &config, translation, &mut rng);
+        }
+    }
+}
+
+fn particles_to_spawn_system(mut commands: Commands, query: Query<(Entity, &ParticlesToSpawn)>) {
+    let mut rng = thread_rng();
+
+    for (entity, particles) in query.iter() {
+        for _ in 0..particles.count {
+            spawn_particle(
+                &mut commands,
+                &particles.config,
+                particles.translation,
+                &mut rng,
+            );
+        }
+
+        commands.entity(entity).despawn();
+    }
+}
+
+fn particle_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut particle, mut transform, mut sprite) in query.iter_mut() {
+        particle.lifetime.tick(time.delta());
+        if particle.lifetime.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation += particle.velocity.extend(0.) * time.delta_seconds();
+
+        // size and color go from their start to their end value over the lifetime
+        let t = particle.lifetime.percent();
+        let size = particle.size.0 + (particle.size.1 - particle.size.0) * t;
+        sprite.custom_size = Some(Vec2::splat(size));
+        sprite.color = lerp_color(particle.color.0, particle.color.1, t);
+    }
+}
+
+fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let [r1, g1, b1, a1] = from.as_rgba_f32();
+    let [r2, g2, b2, a2] = to.as_rgba_f32();
+    Color::rgba(
+        r1 + (r2 - r1) * t,
+        g1 + (g2 - g1) * t,
+        b1 + (b2 - b1) * t,
+        a1 + (a2 - a1) * t,
+    )
+}
@@ -0,0 +1,96 @@
+use bevy::math::Vec3Swizzles;
+use bevy::{prelude::*, sprite::collide_aabb::collide};
+use iyes_loopless::prelude::ConditionSet;
+
+use super::components::{
+    Animation, DespawnEntity, Movable, OffscreenPolicy, Pickup, PickupKind, PickupToSpawn, Player,
+    SpriteSize, Velocity, Weapon,
+};
+use super::constants::{
+    PICKUP_ANIMATION, PICKUP_SIZE, SPRITE_SCALE, WEAPON_MAX_LEVEL, WEAPON_UPGRADE_SIZE,
+};
+use super::resources::{GameTextures, PlayerState};
+use crate::shared::resources::{AppState, UiTextures};
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::Gameplay)
+                .with_system(pickup_to_spawn_system)
+                .with_system(pickup_collect_system)
+                .into(),
+        );
+    }
+}
+
+fn pickup_to_spawn_system(
+    mut commands: Commands,
+    ui_textures: Res<UiTextures>,
+    game_textures: Res<GameTextures>,
+    query: Query<(Entity, &PickupToSpawn)>,
+) {
+    for (pickup_spawn_entity, PickupToSpawn(kind, translation)) in query.iter() {
+        let (texture, size, scale) = match kind {
+            PickupKind::Heal => (ui_textures.heart_full.clone(), PICKUP_SIZE, SPRITE_SCALE),
+            PickupKind::WeaponUpgrade => (
+                game_textures.weapon_upgrade.clone(),
+                WEAPON_UPGRADE_SIZE,
+                SPRITE_SCALE / 2.,
+            ),
+        };
+
+        // pickups slowly drift down towards the player
+        commands
+            .spawn_bundle(SpriteBundle {
+                texture,
+                transform: Transform {
+                    translation: Vec3::new(translation.x, translation.y, 5.),
+                    scale: Vec3::new(scale, scale, 1.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(Pickup(*kind))
+            .insert(Animation::new(PICKUP_ANIMATION))
+            .insert(SpriteSize::from(size))
+            .insert(Movable)
+            .insert(OffscreenPolicy::default())
+            .insert(Velocity { x: 0., y: -0.3 });
+
+        commands.entity(pickup_spawn_entity).despawn();
+    }
+}
+
+fn pickup_collect_system(
+    mut ev_despawn: EventWriter<DespawnEntity>,
+    mut player_state: ResMut<PlayerState>,
+    mut player_query: Query<(&Transform, &SpriteSize, &mut Weapon), With<Player>>,
+    pickup_query: Query<(Entity, &Transform, &SpriteSize, &Pickup)>,
+) {
+    if let Ok((player_tf, player_size, mut weapon)) = player_query.get_single_mut() {
+        for (pickup_entity, pickup_tf, pickup_size, pickup) in pickup_query.iter() {
+            let collision = collide(
+                player_tf.translation,
+                player_size.0 * player_tf.scale.xy(),
+                pickup_tf.translation,
+                pickup_size.0 * pickup_tf.scale.xy(),
+            );
+
+            if collision.is_some() {
+                match pickup.0 {
+                    PickupKind::Heal => player_state.heal(),
+                    PickupKind::WeaponUpgrade => {
+                        weapon.level = (weapon.level + 1).min(WEAPON_MAX_LEVEL);
+                    }
+                }
+
+                ev_despawn.send(DespawnEntity {
+                    entity: pickup_entity,
+                });
+            }
+        }
+    }
+}
@@ -6,42 +6,57 @@ use iyes_loopless::prelude::{
 };
 
 use super::components::{
-    DespawnEntity, EntityType, ExplosionToSpawn, FromEntity, IsHit, IsHittable,
+    Animation, Damage, DamageEvent, DespawnEntity, EntityType, ExplosionToSpawn, HitFlash,
+    IsHittable, Knockback, ParticleConfig, ParticleEmitter, PlayerHit,
 };
 use super::constants::{
-    HIT_DETECTION, HIT_PROCESSING, PLAYER_LASER_SIZE, PLAYER_RESPAWN_DELAY, PLAYER_SIZE,
-    SPRITE_SCALE,
+    HIT_DAMAGE, HIT_PROCESSING, KNOCKBACK_SPEED, PLAYER_COLLISION_DAMAGE, PLAYER_INPUT,
+    PLAYER_RESPAWN_DELAY, PLAYER_SIZE, SHAKE_BIG_EXPLOSION, SHAKE_PLAYER_HIT, SPRITE_SCALE,
+    THRUSTER_ANIMATION,
+};
+use super::resources::{
+    CameraShake, DifficultyStats, GameTextures, GodMode, HitStop, PlayerInput, PlayerRespawn,
+    PlayerState,
 };
-use super::resources::{GameTextures, PlayerState};
 use crate::shared::components::{GameRunning, SpawnPlayer};
-use crate::shared::resources::{AppState, WinSize};
+use crate::shared::resources::{AppState, Difficulty, WinSize};
 use crate::stage_2_gameplay::components::{
-    FiringCooldownTimer, Invincibility, Laser, Movable, Player, SpriteSize, Velocity,
+    DualFighter, FiringCooldownTimer, Invincibility, Movable, OffscreenPolicy, Player, SpriteSize,
+    Velocity, Weapon,
 };
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(
-            player_spawn_system
-                .run_in_state(AppState::Gameplay)
-                .run_if_resource_added::<SpawnPlayer>(),
-        )
-        .add_system_set(
-            ConditionSet::new()
-                .run_in_state(AppState::Gameplay)
-                .with_system(player_keyboard_event_system)
-                .with_system(player_fire_system)
-                .with_system(firing_cooldown_system)
-                .into(),
-        )
-        .add_system(
-            player_hit_system
-                .run_in_state(AppState::Gameplay)
-                .label(HIT_PROCESSING)
-                .after(HIT_DETECTION),
-        );
+        app.add_event::<PlayerHit>()
+            .add_system(
+                player_spawn_system
+                    .run_in_state(AppState::Gameplay)
+                    .run_if_resource_added::<SpawnPlayer>(),
+            )
+            .add_system_set(
+                ConditionSet::new()
+                    .run_in_state(AppState::Gameplay)
+                    .run_unless_resource_exists::<HitStop>()
+                    .after(PLAYER_INPUT)
+                    .with_system(player_movement_system)
+                    .with_system(firing_cooldown_system)
+                    .into(),
+            )
+            .add_system(dual_fighter_system.run_in_state(AppState::Gameplay))
+            .add_system(
+                player_respawn_system
+                    .run_in_state(AppState::Gameplay)
+                    .run_if_resource_exists::<PlayerRespawn>(),
+            )
+            .add_system(
+                player_hit_system
+                    .run_in_state(AppState::Gameplay)
+                    .run_unless_resource_exists::<GodMode>()
+                    .label(HIT_PROCESSING)
+                    .after(HIT_DAMAGE),
+            );
     }
 }
 
@@ -49,14 +64,28 @@ fn player_spawn_system(
     mut commands: Commands,
     game_textures: Res<GameTextures>,
     win_size: Res<WinSize>,
+    difficulty: Res<Difficulty>,
 ) {
     // add player
     let bottom = -win_size.h / 2.; // bottom of the screen
+    let translation = Vec3::new(0., bottom + PLAYER_SIZE.1 / 2. * SPRITE_SCALE + 5., 10.);
+    let invincibility = DifficultyStats::from(*difficulty).invincibility;
+    spawn_player(&mut commands, &game_textures, translation, invincibility);
+
+    commands.remove_resource::<SpawnPlayer>()
+}
+
+pub fn spawn_player(
+    commands: &mut Commands,
+    game_textures: &GameTextures,
+    translation: Vec3,
+    invincibility: f32,
+) -> Entity {
     commands
         .spawn_bundle(SpriteBundle {
             texture: game_textures.player.clone(),
             transform: Transform {
-                translation: Vec3::new(0., bottom + PLAYER_SIZE.1 / 2. * SPRITE_SCALE + 5., 10.),
+                translation,
                 scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
                 ..Default::default()
             },
@@ -65,96 +94,136 @@ fn player_spawn_system(
         .insert(Player)
         .insert(EntityType::Player)
         .insert(SpriteSize::from(PLAYER_SIZE))
-        .insert(Movable {
-            auto_despawn: false,
-        })
+        .insert(Movable)
+        .insert(OffscreenPolicy::Clamp)
         .insert(IsHittable)
         .insert(Velocity { x: 0., y: 0. })
-        .insert(Invincibility::from(3.));
-
-    commands.remove_resource::<SpawnPlayer>()
+        .insert(Weapon::default())
+        .insert(Damage(PLAYER_COLLISION_DAMAGE))
+        .insert(ParticleEmitter::new(
+            ParticleConfig::engine_trail(),
+            40.,
+            Vec2::new(0., -PLAYER_SIZE.1 / 2. * SPRITE_SCALE),
+        ))
+        .insert(Invincibility::from(invincibility))
+        .with_children(|parent| {
+            // engine glow just below the exhaust
+            parent
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgb(1., 0.6, 0.2),
+                        custom_size: Some(Vec2::new(20., 24.)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(0., -PLAYER_SIZE.1 / 2. - 8., -0.1),
+                    ..Default::default()
+                })
+                .insert(Animation::new(THRUSTER_ANIMATION));
+        })
+        .id()
 }
 
-fn player_fire_system(
+// Rescued ship flies wing to wing with the player
+fn dual_fighter_system(
     mut commands: Commands,
-    kb: Res<Input<KeyCode>>,
     game_textures: Res<GameTextures>,
-    query: Query<(Entity, &Transform), (With<Player>, Without<FiringCooldownTimer>)>,
+    query: Query<Entity, Added<DualFighter>>,
 ) {
-    if let Ok((player_entity, player_tf)) = query.get_single() {
-        if kb.just_pressed(KeyCode::Space) {
-            let (x, y) = (player_tf.translation.x, player_tf.translation.y);
-            let x_offset = PLAYER_SIZE.0 / 2. * SPRITE_SCALE - 5.;
-
-            let mut spawn_laser = |x_offset: f32| {
-                commands
-                    .spawn_bundle(SpriteBundle {
-                        texture: game_textures.player_laser.clone(),
-                        transform: Transform {
-                            translation: Vec3::new(x + x_offset, y + 15., 0.),
-                            scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    })
-                    .insert(Movable { auto_despawn: true })
-                    .insert(Velocity { x: 0., y: 1. })
-                    .insert(SpriteSize::from(PLAYER_LASER_SIZE))
-                    .insert(FromEntity::FromPlayer)
-                    .insert(Laser);
-            };
-
-            spawn_laser(x_offset);
-            spawn_laser(-x_offset);
-
-            commands
-                .entity(player_entity)
-                .insert(FiringCooldownTimer::default());
-        }
+    for entity in query.iter() {
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn_bundle(SpriteBundle {
+                texture: game_textures.player.clone(),
+                transform: Transform::from_xyz(PLAYER_SIZE.0, 0., 0.),
+                ..Default::default()
+            });
+        });
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn player_hit_system(
     mut commands: Commands,
     mut ev_despawn: EventWriter<DespawnEntity>,
+    mut ev_damage: EventReader<DamageEvent>,
     mut player_state: ResMut<PlayerState>,
-    query: Query<(Entity, &Transform, &EntityType), (With<Player>, With<IsHit>)>,
+    mut shake: ResMut<CameraShake>,
+    mut ev_hit: EventWriter<PlayerHit>,
+    difficulty: Res<Difficulty>,
+    mut query: Query<(Entity, &Transform, &Sprite, Option<&mut HitFlash>), With<Player>>,
 ) {
-    if let Ok((entity, entity_tf, entity_type)) = query.get_single() {
-        player_state.shot();
+    let player = query.get_single_mut().ok();
+    let player_entity = player.as_ref().map(|(entity, ..)| *entity);
+
+    // everything that hit the player in the same frame counts as a single hit
+    let damage: f32 = ev_damage
+        .iter()
+        .filter(|ev| Some(ev.target) == player_entity)
+        .map(|ev| ev.damage)
+        .sum();
+
+    if let Some((entity, entity_tf, sprite, flash)) = player.filter(|_| damage > 0.) {
+        player_state.shot(damage);
+        ev_hit.send(PlayerHit { damage });
 
         if player_state.health == 0 {
-            ev_despawn.send(DespawnEntity {
-                entity: entity,
-                entity_type: entity_type.clone(),
-            });
+            shake.add_trauma(SHAKE_BIG_EXPLOSION);
+
+            // ship comes back after a delay while there are lives left
+            player_state.lose_life();
+            if player_state.lives > 0 {
+                commands.insert_resource(PlayerRespawn(Timer::from_seconds(
+                    PLAYER_RESPAWN_DELAY,
+                    false,
+                )));
+            }
+
+            ev_despawn.send(DespawnEntity { entity });
 
             commands
                 .spawn()
                 .insert(ExplosionToSpawn(entity_tf.translation));
         } else {
-            commands.entity(entity).remove::<IsHit>();
-            commands.entity(entity).insert(Invincibility::from(3.));
+            shake.add_trauma(SHAKE_PLAYER_HIT);
+
+            let invincibility = DifficultyStats::from(*difficulty).invincibility;
+            commands
+                .entity(entity)
+                .insert(Invincibility::from(invincibility));
+            match flash {
+                Some(mut flash) => flash.timer.reset(),
+                None => {
+                    commands.entity(entity).insert(HitFlash::new(sprite.color));
+                }
+            }
+            commands
+                .entity(entity)
+                .insert(Knockback(Vec2::new(0., -KNOCKBACK_SPEED)));
         }
     }
 }
 
-fn player_keyboard_event_system(
-    kb: Res<Input<KeyCode>>,
-    mut query: Query<&mut Velocity, With<Player>>,
+fn player_respawn_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_state: ResMut<PlayerState>,
+    mut respawn: ResMut<PlayerRespawn>,
 ) {
+    respawn.0.tick(time.delta());
+
+    if respawn.0.finished() {
+        player_state.respawn();
+        commands.insert_resource(SpawnPlayer);
+        commands.remove_resource::<PlayerRespawn>();
+    }
+}
+
+fn player_movement_system(input: Res<PlayerInput>, mut query: Query<&mut Velocity, With<Player>>) {
     if let Ok(mut velocity) = query.get_single_mut() {
-        velocity.x = if kb.pressed(KeyCode::Left) || kb.pressed(KeyCode::A) {
-            -1.
-        } else if kb.pressed(KeyCode::Right) || kb.pressed(KeyCode::D) {
-            1.
-        } else {
-            0.
-        };
+        velocity.x = input.movement;
     }
 }
 
-fn firing_cooldown_system(
+pub fn firing_cooldown_system(
     mut commands: Commands,
     time: Res<Time>,
     mut query: Query<(Entity, &mut FiringCooldownTimer)>,
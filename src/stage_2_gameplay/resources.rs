@@ -1,31 +1,415 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
 use bevy::prelude::*;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use super::components::{EntityType, FromEntity};
+use super::constants::{
+    COLLISION_RULES, COMBO_KILLS_PER_LEVEL, COMBO_MAX_MULTIPLIER, COMBO_TIME, DIRECTOR_HIT_PACE,
+    DIRECTOR_KILL_PACE, DIRECTOR_MAX_TENSION, DIRECTOR_MIN_TENSION, DIRECTOR_RESPONSE,
+    DIRECTOR_WINDOW, ENEMY_MAX, EXTRA_LIFE_SCORE, PLAYER_INVINCIBILITY, PLAYER_LIVES,
+    PLAYER_MAX_HEALTH, PLAYER_MAX_LIVES, WAVE_KILLS,
+};
+use crate::shared::general::load_asset_data;
+use crate::shared::resources::Difficulty;
 
 // Resources
 
 pub struct GameTextures {
     pub player: Handle<Image>,
     pub player_laser: Handle<Image>,
-    pub enemy: Handle<Image>,
+    pub weapon_upgrade: Handle<Image>,
     pub enemy_laser: Handle<Image>,
     pub explosion: Handle<TextureAtlas>,
 }
 
+// Amount of camera shake, decays over time
+#[derive(Default)]
+pub struct CameraShake {
+    pub trauma: f32,
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.);
+    }
+}
+
+// Random numbers that decide how the run plays out, seeded so a saved run resumes the same way
+//
+// Purely cosmetic randomness (particles, stars, shake) doesn't need to go through it
+pub struct GameRng(pub ChaCha12Rng);
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_seed(thread_rng().gen())
+    }
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        GameRng(ChaCha12Rng::seed_from_u64(seed))
+    }
+
+    pub fn state(&self) -> RngState {
+        RngState {
+            seed: self.0.get_seed(),
+            word_pos: self.0.get_word_pos(),
+        }
+    }
+
+    pub fn from_state(state: &RngState) -> Self {
+        let mut rng = ChaCha12Rng::from_seed(state.seed);
+        rng.set_word_pos(state.word_pos);
+        GameRng(rng)
+    }
+}
+
+// Where the generator is at, a generator rebuilt from it goes on with the same numbers
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct RngState {
+    pub seed: [u8; 32],
+    pub word_pos: u128, // numbers already drawn from the seed's stream
+}
+
+// Kills made in quick succession, multiplies the score of every kill while it lasts
+pub struct Combo {
+    pub streak: u32,
+    pub timer: Timer, // runs out when no kill happens in time
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Self {
+            streak: 0,
+            timer: Timer::from_seconds(COMBO_TIME, false),
+        }
+    }
+}
+
+impl Combo {
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.streak / COMBO_KILLS_PER_LEVEL).min(COMBO_MAX_MULTIPLIER)
+    }
+
+    pub fn register_kill(&mut self) {
+        self.streak += 1;
+        self.timer.reset();
+    }
+
+    pub fn reset(&mut self) {
+        *self = Combo::default();
+    }
+
+    pub fn tick(&mut self, delta: Duration) {
+        self.timer.tick(delta);
+        if self.timer.just_finished() {
+            self.streak = 0;
+        }
+    }
+
+    // Part of the combo time that is left, from 0 to 1
+    pub fn remaining(&self) -> f32 {
+        if self.streak == 0 {
+            0.
+        } else {
+            1. - self.timer.percent()
+        }
+    }
+}
+
+// Adaptive difficulty, watches how the player is doing and sets the tension of the run
+//
+// Tension of 1 leaves the game as it is, more means more enemies that fire more often
+pub struct Director {
+    pub tension: f32,
+    pub hits: f32,  // hits taken lately, fades out over the director window
+    pub kills: f32, // kills made lately, fades out over the director window
+}
+
+impl Default for Director {
+    fn default() -> Self {
+        Self {
+            tension: 1.,
+            hits: 0.,
+            // a new run starts as if the player was keeping up
+            kills: DIRECTOR_KILL_PACE * DIRECTOR_WINDOW / 60.,
+        }
+    }
+}
+
+impl Director {
+    pub fn register_hit(&mut self) {
+        self.hits += 1.;
+    }
+
+    pub fn register_kill(&mut self) {
+        self.kills += 1.;
+    }
+
+    pub fn hits_per_minute(&self) -> f32 {
+        self.hits * 60. / DIRECTOR_WINDOW
+    }
+
+    pub fn kills_per_minute(&self) -> f32 {
+        self.kills * 60. / DIRECTOR_WINDOW
+    }
+
+    // Moves tension towards what the players performance asks for
+    //
+    // hearts is the part of the players health that is left, from 0 to 1
+    pub fn update(&mut self, delta: f32, hearts: f32) {
+        let fade = (-delta / DIRECTOR_WINDOW).exp();
+        self.hits *= fade;
+        self.kills *= fade;
+
+        let kill_pace = (self.kills_per_minute() / DIRECTOR_KILL_PACE - 1.).clamp(-1., 1.);
+        let hit_pace = (self.hits_per_minute() / DIRECTOR_HIT_PACE).min(1.);
+        let target = (1. + 0.5 * kill_pace + 0.5 * (hearts - 1.) - 0.5 * hit_pace)
+            .clamp(DIRECTOR_MIN_TENSION, DIRECTOR_MAX_TENSION);
+
+        self.tension += (target - self.tension) * (delta * DIRECTOR_RESPONSE).min(1.);
+    }
+
+    // Enemies of each kind allowed on screen, never less than one
+    pub fn enemy_max(&self, base: u32) -> u32 {
+        ((base as f32 * self.tension).round() as u32).max(1)
+    }
+}
+
+// What something is as far as collisions are concerned
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum CollisionLayer {
+    Player,
+    Asteroid,
+    Minion,
+    PlayerLaser,
+    EnemyLaser,
+}
+
+impl CollisionLayer {
+    pub fn of_entity(entity_type: &EntityType) -> Self {
+        match entity_type {
+            EntityType::Player => CollisionLayer::Player,
+            EntityType::Asteroid => CollisionLayer::Asteroid,
+            EntityType::Minion => CollisionLayer::Minion,
+        }
+    }
+
+    pub fn of_laser(from_entity: &FromEntity) -> Self {
+        match from_entity {
+            FromEntity::FromPlayer => CollisionLayer::PlayerLaser,
+            FromEntity::FromEnemy => CollisionLayer::EnemyLaser,
+        }
+    }
+}
+
+// Source hurts the target when the two touch
+#[derive(Deserialize)]
+struct CollisionRule {
+    source: CollisionLayer,
+    target: CollisionLayer,
+    damage: f32, // multiplier of the source's damage
+}
+
+// Which layers run into each other and how much damage the target takes from the source
+//
+// Rules only go one way, pairs without a rule in either direction pass through each other
+#[derive(Default)]
+pub struct CollisionMatrix(HashMap<(CollisionLayer, CollisionLayer), f32>);
+
+impl CollisionMatrix {
+    pub fn load() -> Self {
+        let rules: Vec<CollisionRule> = load_asset_data(COLLISION_RULES);
+
+        CollisionMatrix(
+            rules
+                .into_iter()
+                .map(|rule| ((rule.source, rule.target), rule.damage))
+                .collect(),
+        )
+    }
+
+    // Damage multiplier for the target when it's hit by the source, None if it isn't affected
+    pub fn damage(&self, source: CollisionLayer, target: CollisionLayer) -> Option<f32> {
+        self.0.get(&(source, target)).copied()
+    }
+
+    pub fn interacts(&self, a: CollisionLayer, b: CollisionLayer) -> bool {
+        self.damage(a, b).is_some() || self.damage(b, a).is_some()
+    }
+}
+
+// What the player wants the ship to do this frame, filled in by the chosen control scheme
+#[derive(Default)]
+pub struct PlayerInput {
+    pub movement: f32, // from -1 (left) to 1 (right)
+    pub fire: bool,    // fire as soon as the weapon is ready
+    pub charge: bool,  // fire button held down, charges the weapon until it's let go
+}
+
+// Counts down until the player's ship comes back after losing a life
+pub struct PlayerRespawn(pub Timer);
+
+// Ship rescued while the player was down, joins the next ship that spawns
+pub struct RescuedShip;
+
+// Gameplay is frozen for a few frames while this resource exists
+pub struct HitStop(pub Timer);
+
+// Player can't be hit while this resource exists, toggled from the console
+pub struct GodMode;
+
+// How fast the run plays compared to normal speed, set from the console
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+// Current wave of the run, advances after enough kills
+pub struct Wave {
+    pub number: u32,
+    pub kills: u32,
+}
+
+impl Default for Wave {
+    fn default() -> Self {
+        Self {
+            number: 1,
+            kills: 0,
+        }
+    }
+}
+
+impl Wave {
+    // Continuing starts the current wave over
+    pub fn restart(&mut self) {
+        self.kills = 0;
+    }
+
+    pub fn register_kill(&mut self) {
+        self.kills += 1;
+        if self.kills >= WAVE_KILLS {
+            self.number += 1;
+            self.kills = 0;
+        }
+    }
+}
+
 pub struct PlayerState {
     pub max_health: usize,
     pub health: usize,
+    pub lives: u32,
+    pub score: u32,
+    pub next_extra_life: u32,
 }
 
 impl Default for PlayerState {
     fn default() -> Self {
+        Self::new(PLAYER_MAX_HEALTH)
+    }
+}
+
+impl PlayerState {
+    pub fn new(max_health: usize) -> Self {
         Self {
-            health: 3,
-            max_health: 3,
+            health: max_health,
+            max_health,
+            lives: PLAYER_LIVES,
+            score: 0,
+            next_extra_life: EXTRA_LIFE_SCORE,
+        }
+    }
+
+    // Every started point of damage costs a heart
+    pub fn shot(&mut self, damage: f32) {
+        let hearts = damage.ceil().max(1.) as usize;
+        self.health = self.health.saturating_sub(hearts);
+    }
+
+    pub fn heal(&mut self) {
+        self.health = (self.health + 1).min(self.max_health);
+    }
+
+    // Losing every heart costs a life
+    pub fn lose_life(&mut self) {
+        self.lives = self.lives.saturating_sub(1);
+    }
+
+    // Ship comes back with full health
+    pub fn respawn(&mut self) {
+        self.health = self.max_health;
+    }
+
+    // Continuing costs half of the score but gives back every life
+    pub fn continue_run(&mut self) {
+        self.score /= 2;
+        self.lives = PLAYER_LIVES;
+        self.health = self.max_health;
+        self.next_extra_life = (self.score / EXTRA_LIFE_SCORE + 1) * EXTRA_LIFE_SCORE;
+    }
+
+    // Awards an extra life every time the score passes the next threshold
+    pub fn add_score(&mut self, points: u32) {
+        self.score += points;
+        while self.score >= self.next_extra_life {
+            self.lives = (self.lives + 1).min(PLAYER_MAX_LIVES);
+            self.next_extra_life += EXTRA_LIFE_SCORE;
         }
     }
 }
 
-impl PlayerState {
-    pub fn shot(&mut self) {
-        self.health -= 1;
+// How the chosen difficulty changes the run, Normal leaves everything as it is
+pub struct DifficultyStats {
+    pub enemy_health: f32, // multiplier
+    pub fire_chance: f64,  // multiplier
+    pub laser_speed: f32,  // multiplier
+    pub enemy_max: u32,    // enemies of each kind on screen
+    pub invincibility: f32,
+    pub max_health: usize,
+}
+
+impl From<Difficulty> for DifficultyStats {
+    fn from(difficulty: Difficulty) -> Self {
+        match difficulty {
+            Difficulty::Easy => DifficultyStats {
+                enemy_health: 0.75,
+                fire_chance: 0.5,
+                laser_speed: 0.75,
+                enemy_max: ENEMY_MAX - 1,
+                invincibility: PLAYER_INVINCIBILITY * 1.5,
+                max_health: PLAYER_MAX_HEALTH + 2,
+            },
+            Difficulty::Normal => DifficultyStats {
+                enemy_health: 1.,
+                fire_chance: 1.,
+                laser_speed: 1.,
+                enemy_max: ENEMY_MAX,
+                invincibility: PLAYER_INVINCIBILITY,
+                max_health: PLAYER_MAX_HEALTH,
+            },
+            Difficulty::Hard => DifficultyStats {
+                enemy_health: 1.5,
+                fire_chance: 1.5,
+                laser_speed: 1.25,
+                enemy_max: ENEMY_MAX + 1,
+                invincibility: PLAYER_INVINCIBILITY * 0.75,
+                max_health: PLAYER_MAX_HEALTH,
+            },
+            Difficulty::Nightmare => DifficultyStats {
+                enemy_health: 2.,
+                fire_chance: 2.,
+                laser_speed: 1.5,
+                enemy_max: ENEMY_MAX + 2,
+                invincibility: PLAYER_INVINCIBILITY * 0.5,
+                max_health: PLAYER_MAX_HEALTH - 1,
+            },
+        }
     }
 }
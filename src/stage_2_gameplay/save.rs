@@ -0,0 +1,342 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::{ConditionHelpers, ConditionSet, IntoConditionalSystem};
+use serde::{Deserialize, Serialize};
+
+use super::combo::spawn_announcement;
+use super::components::{
+    Damage, DualFighter, FromEntity, Homing, Laser, Piercing, Player, Point, ProjectileClash,
+    Velocity, Weapon, WeaponKind,
+};
+use super::constants::PLAYER_RESPAWN_DELAY;
+use super::enemy::archetype::EnemyRegistry;
+use super::enemy::components::{
+    Captive, EnemyKind, EnemyMovement, EnemyStats, EnemyWeapon, SplitShot,
+};
+use super::enemy::dive::capture_ship;
+use super::enemy::minion::spawn_enemy_laser;
+use super::enemy::spawn_enemy;
+use super::player::spawn_player;
+use super::resources::{
+    DifficultyStats, GameRng, GameTextures, PlayerRespawn, PlayerState, RescuedShip, RngState, Wave,
+};
+use super::weapon::{spawn_projectile, ProjectileSpec};
+use crate::shared::components::{ExitGameButton, LoadGame, SpawnPlayer};
+use crate::shared::constants::SAVE_FILE;
+use crate::shared::general::{
+    delete_save_data, on_button_interact, read_save_data, write_save_data,
+};
+use crate::shared::resources::{AppState, Continues, Difficulty, MenuDifficulty, UiTextures};
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        // leaving through the pause menu keeps the run around for later
+        app.add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::Paused)
+                .run_if(on_button_interact::<ExitGameButton>)
+                .with_system(save_game_system)
+                .into(),
+        )
+        .add_system(
+            load_game_system
+                .run_in_state(AppState::Gameplay)
+                .run_if_resource_exists::<LoadGame>()
+                .run_if_resource_exists::<EnemyRegistry>(),
+        );
+    }
+}
+
+// Everything needed to pick a run back up
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    pub player: Option<SavedPlayer>,
+    pub health: usize,
+    pub max_health: usize,
+    pub lives: u32,
+    pub score: u32,
+    pub next_extra_life: u32,
+    pub continues: u32,
+    pub wave: u32,
+    pub wave_kills: u32,
+    pub enemies: Vec<SavedEnemy>,
+    pub lasers: Vec<SavedLaser>,
+    pub rng: RngState,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub rescued_ship: bool, // joins the player's next ship
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedPlayer {
+    pub position: Point,
+    pub weapon: WeaponKind,
+    pub weapon_level: u8,
+    #[serde(default)]
+    pub dual_fighter: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedEnemy {
+    pub kind: String,
+    pub position: Point,
+    pub movement: EnemyMovement,
+    pub stats: EnemyStats,
+    #[serde(default)]
+    pub volleys: u32,
+    #[serde(default)]
+    pub captive: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedLaser {
+    pub position: Point,
+    pub velocity: (f32, f32),
+    pub damage: f32,
+    pub from_player: bool,
+    pub scale: (f32, f32),
+    pub color: [f32; 4],
+    pub piercing: bool,
+    pub homing: bool,
+    #[serde(default)]
+    pub clash: ProjectileClash,
+    #[serde(default)]
+    pub split: Option<SplitShot>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_game_system(
+    player_state: Res<PlayerState>,
+    continues: Res<Continues>,
+    wave: Res<Wave>,
+    difficulty: Res<Difficulty>,
+    rescued_ship: Option<Res<RescuedShip>>,
+    rng: Res<GameRng>,
+    player_query: Query<(&Transform, &Weapon, Option<&DualFighter>), With<Player>>,
+    enemy_query: Query<(
+        &Transform,
+        &EnemyKind,
+        &EnemyMovement,
+        &EnemyStats,
+        Option<&EnemyWeapon>,
+        Option<&Captive>,
+    )>,
+    laser_query: Query<
+        (
+            &Transform,
+            &Velocity,
+            &Damage,
+            &FromEntity,
+            &Sprite,
+            Option<&Piercing>,
+            Option<&Homing>,
+            Option<&ProjectileClash>,
+            Option<&SplitShot>,
+        ),
+        With<Laser>,
+    >,
+) {
+    let point = |tf: &Transform| Point {
+        x: tf.translation.x,
+        y: tf.translation.y,
+    };
+
+    let player = player_query
+        .get_single()
+        .ok()
+        .map(|(tf, weapon, dual_fighter)| SavedPlayer {
+            position: point(tf),
+            weapon: weapon.kind,
+            weapon_level: weapon.level,
+            dual_fighter: dual_fighter.is_some(),
+        });
+
+    let enemies = enemy_query
+        .iter()
+        .map(|(tf, kind, movement, stats, weapon, captive)| SavedEnemy {
+            kind: kind.0.clone(),
+            position: point(tf),
+            movement: movement.clone(),
+            stats: stats.clone(),
+            volleys: weapon.map_or(0, |weapon| weapon.volleys),
+            captive: captive.is_some(),
+        })
+        .collect();
+
+    let lasers = laser_query
+        .iter()
+        .map(
+            |(tf, velocity, damage, from, sprite, piercing, homing, clash, split)| SavedLaser {
+                position: point(tf),
+                velocity: (velocity.x, velocity.y),
+                damage: damage.0,
+                from_player: matches!(from, FromEntity::FromPlayer),
+                scale: (tf.scale.x, tf.scale.y),
+                color: sprite.color.as_rgba_f32(),
+                piercing: piercing.is_some(),
+                homing: homing.is_some(),
+                clash: clash.copied().unwrap_or_default(),
+                split: split.cloned(),
+            },
+        )
+        .collect();
+
+    write_save_data(
+        SAVE_FILE,
+        &SaveGame {
+            player,
+            health: player_state.health,
+            max_health: player_state.max_health,
+            lives: player_state.lives,
+            score: player_state.score,
+            next_extra_life: player_state.next_extra_life,
+            continues: continues.0,
+            wave: wave.number,
+            wave_kills: wave.kills,
+            enemies,
+            lasers,
+            rng: rng.state(),
+            difficulty: *difficulty,
+            rescued_ship: rescued_ship.is_some(),
+        },
+    );
+}
+
+fn load_game_system(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    ui_textures: Res<UiTextures>,
+    registry: Res<EnemyRegistry>,
+    difficulty: Res<Difficulty>,
+) {
+    commands.remove_resource::<LoadGame>();
+
+    let save: SaveGame = match read_save_data(SAVE_FILE) {
+        Some(save) => save,
+        None => {
+            // nothing to resume, start a fresh run instead
+            commands.insert_resource(SpawnPlayer);
+            return;
+        }
+    };
+
+    // a save can only be resumed once
+    delete_save_data(SAVE_FILE);
+
+    commands.insert_resource(PlayerState {
+        max_health: save.max_health,
+        health: save.health,
+        lives: save.lives,
+        score: save.score,
+        next_extra_life: save.next_extra_life,
+    });
+    commands.insert_resource(Continues(save.continues));
+    commands.insert_resource(Wave {
+        number: save.wave,
+        kills: save.wave_kills,
+    });
+    commands.insert_resource(GameRng::from_state(&save.rng));
+
+    // the run goes on at the difficulty it was started on, the menu gets its pick back afterwards
+    commands.insert_resource(MenuDifficulty(*difficulty));
+    commands.insert_resource(save.difficulty);
+    spawn_announcement(
+        &mut commands,
+        &ui_textures,
+        format!("{} run resumed", save.difficulty.name()),
+    );
+    if save.rescued_ship {
+        commands.insert_resource(RescuedShip);
+    }
+
+    match save.player {
+        Some(player) => {
+            let translation = Vec3::new(player.position.x, player.position.y, 10.);
+            let invincibility = DifficultyStats::from(save.difficulty).invincibility;
+            let entity = spawn_player(&mut commands, &game_textures, translation, invincibility);
+            commands.entity(entity).insert(Weapon {
+                kind: player.weapon,
+                level: player.weapon_level,
+                ..Default::default()
+            });
+            if player.dual_fighter {
+                commands.entity(entity).insert(DualFighter);
+            }
+        }
+        // saved while waiting for the next life
+        None => commands.insert_resource(PlayerRespawn(Timer::from_seconds(
+            PLAYER_RESPAWN_DELAY,
+            false,
+        ))),
+    }
+
+    for enemy in save.enemies {
+        let registered = match registry.get(&enemy.kind) {
+            Some(registered) => registered,
+            None => {
+                warn!("Unknown enemy archetype in save: {}", enemy.kind);
+                continue;
+            }
+        };
+
+        let entity = spawn_enemy(
+            &mut commands,
+            &enemy.kind,
+            registered,
+            enemy.position,
+            enemy.movement.state.clone(),
+            save.difficulty,
+        );
+        commands
+            .entity(entity)
+            .insert(enemy.movement)
+            .insert(enemy.stats);
+        if let Some(weapon) = &registered.archetype.weapon {
+            commands.entity(entity).insert(EnemyWeapon {
+                volleys: enemy.volleys,
+                ..weapon.clone()
+            });
+        }
+        if enemy.captive {
+            capture_ship(&mut commands, &game_textures, entity);
+        }
+    }
+
+    for laser in save.lasers {
+        let translation = Vec3::new(laser.position.x, laser.position.y, 0.);
+        let velocity = Vec2::new(laser.velocity.0, laser.velocity.1);
+
+        if laser.from_player {
+            let [r, g, b, a] = laser.color;
+            spawn_projectile(
+                &mut commands,
+                &game_textures,
+                translation,
+                Color::rgba(r, g, b, a),
+                &ProjectileSpec {
+                    offset: Vec2::ZERO,
+                    velocity,
+                    damage: laser.damage,
+                    scale: Vec2::new(laser.scale.0, laser.scale.1),
+                    piercing: laser.piercing,
+                    homing: laser.homing,
+                    clash: laser.clash,
+                },
+            );
+        } else {
+            let entity = spawn_enemy_laser(
+                &mut commands,
+                &game_textures,
+                translation,
+                velocity,
+                laser.damage,
+                laser.clash,
+            );
+            if let Some(split) = laser.split {
+                commands.entity(entity).insert(split);
+            }
+        }
+    }
+}
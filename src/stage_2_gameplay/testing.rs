@@ -0,0 +1,81 @@
+use bevy::ecs::event::Events;
+use bevy::ecs::schedule::{IntoSystemDescriptor, ParallelSystemDescriptorCoercion};
+use bevy::ecs::system::Resource;
+use bevy::prelude::*;
+
+use super::components::{DamageEvent, DespawnEntity};
+use super::enemy::components::{EnemyCount, EnemyKilled};
+use super::enemy::path::EnemyPaths;
+use super::resources::{CameraShake, GameRng, PlayerInput, PlayerState, TimeScale};
+use crate::shared::resources::WinSize;
+
+#[derive(SystemLabel, Clone, Debug, PartialEq, Eq, Hash)]
+struct Step(usize);
+
+// Gameplay resources and events without the app, its plugins or a window
+//
+// Systems added to it run once per frame in the order they were added
+pub struct TestWorld {
+    pub world: World,
+    stage: SystemStage,
+    steps: usize,
+}
+
+impl TestWorld {
+    pub fn new() -> Self {
+        let mut world = World::new();
+        world.insert_resource(Time::default());
+        world.insert_resource(WinSize { w: 600., h: 600. });
+        world.insert_resource(TimeScale::default());
+        world.insert_resource(PlayerInput::default());
+        world.insert_resource(PlayerState::default());
+        world.insert_resource(CameraShake::default());
+        world.insert_resource(GameRng::default());
+        world.insert_resource(EnemyPaths::default());
+        world.insert_resource(EnemyCount::default());
+
+        let mut test = Self {
+            world,
+            stage: SystemStage::single_threaded(),
+            steps: 0,
+        };
+        test.add_event::<DespawnEntity>()
+            .add_event::<DamageEvent>()
+            .add_event::<EnemyKilled>();
+        test
+    }
+
+    pub fn add_event<E: Resource>(&mut self) -> &mut Self {
+        self.world.insert_resource(Events::<E>::default());
+        self.add_system(Events::<E>::update_system)
+    }
+
+    pub fn add_system<Params>(
+        &mut self,
+        system: impl ParallelSystemDescriptorCoercion<Params>,
+    ) -> &mut Self {
+        let system = system.label(Step(self.steps));
+        let system = match self.steps {
+            0 => system,
+            step => system.after(Step(step - 1)),
+        };
+        self.stage.add_system(system);
+        self.steps += 1;
+        self
+    }
+
+    pub fn send<E: Resource>(&mut self, event: E) {
+        self.world.resource_mut::<Events<E>>().send(event);
+    }
+
+    // Runs a system once on its own, outside of the frame
+    pub fn run<Params>(&mut self, system: impl IntoSystemDescriptor<Params>) {
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(system);
+        stage.run(&mut self.world);
+    }
+
+    pub fn frame(&mut self) {
+        self.stage.run(&mut self.world);
+    }
+}
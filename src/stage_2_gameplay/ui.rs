@@ -4,10 +4,15 @@ use iyes_loopless::prelude::{
 };
 use iyes_loopless::state::NextState;
 
-use crate::shared::components::{ExitGameButton, GameRunning, GameplayTeardown, ResetGameplay};
+use crate::shared::components::{
+    ContinueGameplay, ExitGameButton, GameRunning, GameplayTeardown, ResetGameplay,
+};
 use crate::shared::general::{esc_pressed, on_button_interact};
 use crate::shared::resources::{AppState, UiTextures, WinSize};
-use crate::stage_2_gameplay::components::HeartImage;
+use crate::stage_2_gameplay::components::{
+    ComboMeter, ComboText, DespawnEntity, HeartImage, LivesText, Player, ScoreText, Weapon,
+    WeaponText,
+};
 use crate::stage_2_gameplay::resources::PlayerState;
 
 pub struct UiPlugin;
@@ -26,13 +31,20 @@ impl Plugin for UiPlugin {
                 .run_in_state(AppState::Gameplay)
                 // updates players current health
                 .with_system(heart_image_update_system)
+                // updates players current score
+                .with_system(score_text_update_system)
+                // updates players remaining lives
+                .with_system(lives_text_update_system)
+                // updates players current weapon
+                .with_system(weapon_text_update_system)
                 // esc pauses the game
                 .with_system(pause_system.run_if(esc_pressed))
                 // when player dies the game over screen pops up
                 .with_system(
                     game_over_system
                         .run_if(has_player_died)
-                        .run_unless_resource_exists::<ResetGameplay>(),
+                        .run_unless_resource_exists::<ResetGameplay>()
+                        .run_unless_resource_exists::<ContinueGameplay>(),
                 )
                 .into(),
         )
@@ -60,7 +72,7 @@ fn setup_ui_system(
         .spawn_bundle(NodeBundle {
             style: Style {
                 size: Size::new(Val::Percent(100.), Val::Percent(100.)),
-                justify_content: JustifyContent::FlexStart,
+                justify_content: JustifyContent::SpaceBetween,
                 ..Default::default()
             },
             color: Color::NONE.into(),
@@ -88,6 +100,108 @@ fn setup_ui_system(
                         parent.spawn_bundle(bundle).insert(HeartImage);
                     }
                 });
+
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(250.), Val::Percent(100.)),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::FlexEnd,
+                        justify_content: JustifyContent::FlexEnd,
+                        border: Rect::all(Val::Px(10.)),
+                        ..Default::default()
+                    },
+                    color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    // columns are laid out bottom to top so lives and weapon go under the score
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(100.), Val::Px(6.)),
+                                margin: Rect {
+                                    top: Val::Px(4.),
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            },
+                            color: Color::rgba(1., 1., 1., 0.15).into(),
+                            ..Default::default()
+                        })
+                        .with_children(|parent| {
+                            parent
+                                .spawn_bundle(NodeBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                                        ..Default::default()
+                                    },
+                                    color: Color::rgb(1., 0.8, 0.2).into(),
+                                    ..Default::default()
+                                })
+                                .insert(ComboMeter);
+                        });
+
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                "",
+                                TextStyle {
+                                    font: ui_textures.ui_font.clone(),
+                                    font_size: 20.0,
+                                    color: Color::rgb(1., 0.8, 0.2),
+                                },
+                                Default::default(),
+                            ),
+                            ..default()
+                        })
+                        .insert(ComboText);
+
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                lives_label(&player_state),
+                                TextStyle {
+                                    font: ui_textures.ui_font.clone(),
+                                    font_size: 20.0,
+                                    color: Color::rgb(0.9, 0.9, 0.9),
+                                },
+                                Default::default(),
+                            ),
+                            ..default()
+                        })
+                        .insert(LivesText);
+
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                "",
+                                TextStyle {
+                                    font: ui_textures.ui_font.clone(),
+                                    font_size: 20.0,
+                                    color: Color::rgb(0.9, 0.9, 0.9),
+                                },
+                                Default::default(),
+                            ),
+                            ..default()
+                        })
+                        .insert(WeaponText);
+
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                player_state.score.to_string(),
+                                TextStyle {
+                                    font: ui_textures.ui_font.clone(),
+                                    font_size: 40.0,
+                                    color: Color::rgb(0.9, 0.9, 0.9),
+                                },
+                                Default::default(),
+                            ),
+                            ..default()
+                        })
+                        .insert(ScoreText);
+                });
         });
 }
 
@@ -116,6 +230,47 @@ fn heart_image_update_system(
     }
 }
 
+fn score_text_update_system(
+    player_state: Res<PlayerState>,
+    mut query: Query<&mut Text, With<ScoreText>>,
+) {
+    if !player_state.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        text.sections[0].value = player_state.score.to_string();
+    }
+}
+
+fn lives_label(player_state: &PlayerState) -> String {
+    format!("Lives x{}", player_state.lives)
+}
+
+fn lives_text_update_system(
+    player_state: Res<PlayerState>,
+    mut query: Query<&mut Text, With<LivesText>>,
+) {
+    if !player_state.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        text.sections[0].value = lives_label(&player_state);
+    }
+}
+
+fn weapon_text_update_system(
+    weapon_query: Query<&Weapon, (With<Player>, Changed<Weapon>)>,
+    mut query: Query<&mut Text, With<WeaponText>>,
+) {
+    if let Ok(weapon) = weapon_query.get_single() {
+        for mut text in query.iter_mut() {
+            text.sections[0].value = format!("{} Lv{}", weapon.kind.stats().name, weapon.level);
+        }
+    }
+}
+
 fn pause_system(mut commands: Commands) {
     commands.insert_resource(NextState(AppState::Paused));
 }
@@ -127,7 +282,7 @@ fn game_over_system(mut commands: Commands) {
 }
 
 fn has_player_died(mut commands: Commands, player_state: Res<PlayerState>) -> bool {
-    player_state.health == 0
+    player_state.lives == 0
 }
 
 // Gameplay teardown and state change to MainMenu
@@ -137,9 +292,14 @@ fn gameplay_to_clean_up_system(mut commands: Commands) {
     commands.insert_resource(GameplayTeardown);
 }
 
-fn teardown_system<T: Component>(mut commands: Commands, query: Query<Entity, Without<(Camera)>>) {
+pub(super) fn teardown_system<T: Component>(
+    mut commands: Commands,
+    mut ev_despawn: EventWriter<DespawnEntity>,
+    query: Query<Entity, Without<(Camera)>>,
+) {
+    // what's left of the run goes through the despawn pipeline like every other despawn
     for entity in query.iter() {
-        commands.entity(entity).despawn_recursive();
+        ev_despawn.send(DespawnEntity { entity });
     }
 
     commands.remove_resource::<T>()
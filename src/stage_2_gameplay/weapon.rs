@@ -0,0 +1,413 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use iyes_loopless::prelude::{ConditionSet, IntoConditionalSystem};
+
+use super::components::{
+    Damage, DualFighter, FiringCooldownTimer, FromEntity, Homing, Laser, Movable, OffscreenPolicy,
+    Piercing, Player, ProjectileClash, SpriteSize, Velocity, Weapon, WeaponKind,
+};
+use super::console::Console;
+use super::constants::{
+    CHARGE_THRESHOLD, CHARGE_TIME, PLAYER_INPUT, PLAYER_LASER_SIZE, PLAYER_SIZE, SPRITE_SCALE,
+};
+use super::enemy::components::Enemy;
+use super::resources::{GameTextures, HitStop, PlayerInput};
+use crate::shared::resources::AppState;
+
+pub struct WeaponPlugin;
+
+impl Plugin for WeaponPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            ConditionSet::new()
+                .run_in_state(AppState::Gameplay)
+                .run_unless_resource_exists::<HitStop>()
+                .after(PLAYER_INPUT)
+                .with_system(weapon_switch_system.run_unless_resource_exists::<Console>())
+                .with_system(player_fire_system)
+                .with_system(homing_system)
+                .into(),
+        );
+    }
+}
+
+pub struct WeaponStats {
+    pub name: &'static str,
+    pub color: Color,
+    pub damage: f32,
+    pub cooldown: f32, // seconds between shots
+    pub speed: f32,
+    pub clash: ProjectileClash, // against enemy projectiles
+}
+
+impl WeaponKind {
+    pub const ALL: [WeaponKind; 5] = [
+        WeaponKind::TwinLaser,
+        WeaponKind::SpreadShot,
+        WeaponKind::PiercingBeam,
+        WeaponKind::HomingMissile,
+        WeaponKind::ChargeShot,
+    ];
+
+    pub fn stats(&self) -> WeaponStats {
+        match self {
+            WeaponKind::TwinLaser => WeaponStats {
+                name: "Twin laser",
+                color: Color::WHITE,
+                damage: 1.,
+                cooldown: 0.5,
+                speed: 1.,
+                clash: ProjectileClash::CANCELS,
+            },
+            WeaponKind::SpreadShot => WeaponStats {
+                name: "Spread shot",
+                color: Color::rgb(1., 0.6, 0.2),
+                damage: 0.75,
+                cooldown: 0.6,
+                speed: 0.9,
+                clash: ProjectileClash::CANCELS,
+            },
+            WeaponKind::PiercingBeam => WeaponStats {
+                name: "Piercing beam",
+                color: Color::rgb(0.4, 0.9, 1.),
+                damage: 2.,
+                cooldown: 0.8,
+                speed: 1.6,
+                clash: ProjectileClash::INDESTRUCTIBLE,
+            },
+            WeaponKind::HomingMissile => WeaponStats {
+                name: "Homing missiles",
+                color: Color::rgb(0.5, 1., 0.5),
+                damage: 1.,
+                cooldown: 0.7,
+                speed: 0.7,
+                clash: ProjectileClash::CANCELS,
+            },
+            WeaponKind::ChargeShot => WeaponStats {
+                name: "Charge shot",
+                color: Color::rgb(1., 0.4, 1.),
+                damage: 1.,
+                cooldown: 0.3,
+                speed: 1.2,
+                clash: ProjectileClash::CANCELS,
+            },
+        }
+    }
+
+    // Returns the weapon that comes after this one, wraps around at the end
+    pub fn cycle(&self, step: isize) -> WeaponKind {
+        let len = WeaponKind::ALL.len() as isize;
+        let idx = WeaponKind::ALL
+            .iter()
+            .position(|kind| kind == self)
+            .unwrap() as isize;
+        WeaponKind::ALL[(idx + step).rem_euclid(len) as usize]
+    }
+}
+
+// Everything needed to spawn a single projectile relative to the player
+pub struct ProjectileSpec {
+    pub offset: Vec2,
+    pub velocity: Vec2,
+    pub damage: f32,
+    pub scale: Vec2,
+    pub piercing: bool,
+    pub homing: bool,
+    pub clash: ProjectileClash,
+}
+
+impl ProjectileSpec {
+    fn straight(offset: Vec2, stats: &WeaponStats) -> Self {
+        ProjectileSpec {
+            offset,
+            velocity: Vec2::new(0., stats.speed),
+            damage: stats.damage,
+            scale: Vec2::splat(SPRITE_SCALE),
+            piercing: false,
+            homing: false,
+            clash: stats.clash,
+        }
+    }
+
+    fn angled(offset: Vec2, angle: f32, stats: &WeaponStats) -> Self {
+        ProjectileSpec {
+            velocity: Vec2::new(angle.sin(), angle.cos()) * stats.speed,
+            ..ProjectileSpec::straight(offset, stats)
+        }
+    }
+}
+
+impl Weapon {
+    // Projectile pattern of the weapon at its current level
+    //
+    // Charge goes from 0 to 1, the charge shot is built around it and gets the most out of it
+    pub fn projectiles(&self, charge: f32) -> Vec<ProjectileSpec> {
+        let stats = self.kind.stats();
+        let level = self.level as f32;
+        let x_offset = PLAYER_SIZE.0 / 2. * SPRITE_SCALE - 5.;
+
+        let mut projectiles = match self.kind {
+            WeaponKind::TwinLaser => {
+                let mut specs = vec![
+                    ProjectileSpec::straight(Vec2::new(x_offset, 15.), &stats),
+                    ProjectileSpec::straight(Vec2::new(-x_offset, 15.), &stats),
+                ];
+                if self.level >= 2 {
+                    specs.push(ProjectileSpec::straight(Vec2::new(0., 25.), &stats));
+                }
+                if self.level >= 3 {
+                    specs.push(ProjectileSpec::angled(
+                        Vec2::new(x_offset, 15.),
+                        0.2,
+                        &stats,
+                    ));
+                    specs.push(ProjectileSpec::angled(
+                        Vec2::new(-x_offset, 15.),
+                        -0.2,
+                        &stats,
+                    ));
+                }
+                specs
+            }
+            WeaponKind::SpreadShot => {
+                // 3, 5 or 7 shots fanned out evenly
+                let count = 1 + 2 * self.level as i32;
+                let step = 0.18;
+                (0..count)
+                    .map(|i| {
+                        let angle = (i - count / 2) as f32 * step;
+                        ProjectileSpec::angled(Vec2::new(0., 20.), angle, &stats)
+                    })
+                    .collect()
+            }
+            WeaponKind::PiercingBeam => vec![ProjectileSpec {
+                damage: stats.damage * (1. + 0.5 * (level - 1.)),
+                scale: Vec2::new(SPRITE_SCALE * (1. + level / 2.), SPRITE_SCALE * 2.),
+                piercing: true,
+                ..ProjectileSpec::straight(Vec2::new(0., 30.), &stats)
+            }],
+            WeaponKind::HomingMissile => (0..self.level)
+                .map(|i| {
+                    // missiles leave the ship alternating left and right
+                    let side = if i % 2 == 0 { 1. } else { -1. };
+                    let angle = side * (0.4 + 0.2 * (i / 2) as f32);
+                    ProjectileSpec {
+                        homing: true,
+                        ..ProjectileSpec::angled(Vec2::new(side * x_offset, 10.), angle, &stats)
+                    }
+                })
+                .collect(),
+            WeaponKind::ChargeShot => {
+                // fully charged shot deals up to 4 times the damage, more with each level
+                let multiplier = 1. + charge * (2. + level);
+                vec![ProjectileSpec {
+                    damage: stats.damage * multiplier,
+                    scale: Vec2::splat(SPRITE_SCALE * (1. + charge)),
+                    piercing: charge >= 1.,
+                    // fully charged shots plow through enemy fire
+                    clash: if charge >= 1. {
+                        ProjectileClash::INDESTRUCTIBLE
+                    } else {
+                        stats.clash
+                    },
+                    ..ProjectileSpec::straight(Vec2::new(0., 25.), &stats)
+                }]
+            }
+        };
+
+        // charged volleys of the other weapons hit harder, fully charged ones plow through
+        if charge > 0. && self.kind != WeaponKind::ChargeShot {
+            for spec in projectiles.iter_mut() {
+                spec.damage *= 1. + charge;
+                spec.scale *= 1. + charge / 2.;
+                if charge >= 1. {
+                    spec.piercing = true;
+                    spec.clash = ProjectileClash::INDESTRUCTIBLE;
+                }
+            }
+        }
+
+        projectiles
+    }
+}
+
+pub fn spawn_projectile(
+    commands: &mut Commands,
+    game_textures: &GameTextures,
+    origin: Vec3,
+    color: Color,
+    spec: &ProjectileSpec,
+) {
+    let mut projectile = commands.spawn_bundle(SpriteBundle {
+        texture: game_textures.player_laser.clone(),
+        sprite: Sprite {
+            color,
+            ..Default::default()
+        },
+        transform: Transform {
+            translation: Vec3::new(origin.x + spec.offset.x, origin.y + spec.offset.y, 0.),
+            rotation: Quat::from_rotation_z(-spec.velocity.x.atan2(spec.velocity.y)),
+            scale: spec.scale.extend(1.),
+        },
+        ..Default::default()
+    });
+
+    projectile
+        .insert(Movable)
+        .insert(OffscreenPolicy::default())
+        .insert(Velocity {
+            x: spec.velocity.x,
+            y: spec.velocity.y,
+        })
+        .insert(SpriteSize::from(PLAYER_LASER_SIZE))
+        .insert(FromEntity::FromPlayer)
+        .insert(Laser)
+        .insert(Damage(spec.damage))
+        .insert(spec.clash);
+
+    if spec.piercing {
+        projectile.insert(Piercing::default());
+    }
+    if spec.homing {
+        projectile.insert(Homing { turn_rate: 4. });
+    }
+}
+
+fn weapon_switch_system(kb: Res<Input<KeyCode>>, mut query: Query<&mut Weapon, With<Player>>) {
+    if let Ok(mut weapon) = query.get_single_mut() {
+        let number_keys = [
+            KeyCode::Key1,
+            KeyCode::Key2,
+            KeyCode::Key3,
+            KeyCode::Key4,
+            KeyCode::Key5,
+        ];
+
+        let next_kind = if kb.just_pressed(KeyCode::Q) {
+            Some(weapon.kind.cycle(-1))
+        } else if kb.just_pressed(KeyCode::E) {
+            Some(weapon.kind.cycle(1))
+        } else {
+            number_keys
+                .iter()
+                .position(|key| kb.just_pressed(*key))
+                .map(|idx| WeaponKind::ALL[idx])
+        };
+
+        if let Some(kind) = next_kind {
+            if kind != weapon.kind {
+                weapon.kind = kind;
+                weapon.charge = 0.;
+            }
+        }
+    }
+}
+
+pub fn player_fire_system(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    time: Res<Time>,
+    game_textures: Res<GameTextures>,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Weapon,
+            Option<&DualFighter>,
+            Option<&FiringCooldownTimer>,
+        ),
+        With<Player>,
+    >,
+) {
+    if let Ok((player_entity, player_tf, mut weapon, dual, cooldown)) = query.get_single_mut() {
+        if input.charge {
+            weapon.charge =
+                (weapon.charge + time.delta_seconds()).min(CHARGE_THRESHOLD + CHARGE_TIME);
+        }
+
+        // holding fire past the threshold charges the weapon and regular shots wait until it's let
+        // go, the charge shot holds its shot back from the moment fire is pressed
+        let threshold = if weapon.kind == WeaponKind::ChargeShot {
+            0.
+        } else {
+            CHARGE_THRESHOLD
+        };
+        let charging = weapon.charge > threshold;
+        if !input.charge && !charging {
+            weapon.charge = 0.;
+        }
+
+        // a charge let go of during the cooldown is kept until the weapon is ready again
+        if cooldown.is_some() {
+            return;
+        }
+
+        let charge = if charging && !input.charge {
+            let charge = ((weapon.charge - threshold) / CHARGE_TIME).min(1.);
+            weapon.charge = 0.;
+            charge
+        } else if input.fire && !charging {
+            0.
+        } else {
+            return;
+        };
+
+        // the second fighter fires the same shots from its own position
+        let mut origins = vec![player_tf.translation];
+        if dual.is_some() {
+            origins.push(player_tf.translation + Vec3::new(PLAYER_SIZE.0 * SPRITE_SCALE, 0., 0.));
+        }
+
+        let stats = weapon.kind.stats();
+        for spec in weapon.projectiles(charge) {
+            for origin in origins.iter() {
+                spawn_projectile(&mut commands, &game_textures, *origin, stats.color, &spec);
+            }
+        }
+
+        commands
+            .entity(player_entity)
+            .insert(FiringCooldownTimer(Timer::from_seconds(
+                stats.cooldown,
+                false,
+            )));
+    }
+}
+
+fn homing_system(
+    time: Res<Time>,
+    enemy_query: Query<&Transform, With<Enemy>>,
+    mut query: Query<(&mut Velocity, &mut Transform, &Homing), Without<Enemy>>,
+) {
+    for (mut velocity, mut transform, homing) in query.iter_mut() {
+        let position = transform.translation.truncate();
+
+        // closest enemy is the target
+        let target = enemy_query
+            .iter()
+            .map(|enemy_tf| enemy_tf.translation.truncate())
+            .min_by(|a, b| {
+                a.distance_squared(position)
+                    .partial_cmp(&b.distance_squared(position))
+                    .unwrap()
+            });
+
+        if let Some(target) = target {
+            let current = Vec2::new(velocity.x, velocity.y);
+            let speed = current.length();
+
+            // rotate the velocity towards the target by at most turn_rate per second
+            let desired_angle = (target - position).angle_between(Vec2::Y);
+            let current_angle = current.angle_between(Vec2::Y);
+            let max_turn = homing.turn_rate * time.delta_seconds();
+            let diff = (desired_angle - current_angle + PI).rem_euclid(2. * PI) - PI;
+            let turn = diff.clamp(-max_turn, max_turn);
+            let angle = current_angle + turn;
+
+            velocity.x = angle.sin() * speed;
+            velocity.y = angle.cos() * speed;
+            transform.rotation = Quat::from_rotation_z(-angle);
+        }
+    }
+}
@@ -5,3 +5,15 @@ pub struct PauseMenu;
 
 #[derive(Component)]
 pub struct ResumeGameButton;
+
+#[derive(Component)]
+pub struct ScreenShakeButton;
+
+#[derive(Component)]
+pub struct DirectorButton;
+
+#[derive(Component)]
+pub struct ControlsButton;
+
+#[derive(Component)]
+pub struct FireModeButton;
@@ -6,12 +6,16 @@ use iyes_loopless::{
 
 use crate::shared::{
     components::{ExitGameButton, GameRunning, GameplayTeardown},
-    constants::NORMAL_BUTTON,
-    general::{button_color_system, despawn_system, esc_pressed, on_button_interact},
-    resources::{AppState, UiTextures},
+    general::{
+        button_color_system, button_label_system, despawn_system, esc_pressed, on_button_interact,
+        spawn_menu_button,
+    },
+    resources::{AppState, ControlScheme, FireMode, Settings, UiTextures},
 };
 
-use super::components::{PauseMenu, ResumeGameButton};
+use super::components::{
+    ControlsButton, DirectorButton, FireModeButton, PauseMenu, ResumeGameButton, ScreenShakeButton,
+};
 
 pub struct PauseMenuPlugin;
 
@@ -26,6 +30,26 @@ impl Plugin for PauseMenuPlugin {
                         resume_gameplay_system.run_if(on_button_interact::<ResumeGameButton>),
                     )
                     .with_system(resume_gameplay_system.run_if(esc_pressed))
+                    .with_system(
+                        toggle_screen_shake_system
+                            .chain(button_label_system::<ScreenShakeButton>)
+                            .run_if(on_button_interact::<ScreenShakeButton>),
+                    )
+                    .with_system(
+                        toggle_director_system
+                            .chain(button_label_system::<DirectorButton>)
+                            .run_if(on_button_interact::<DirectorButton>),
+                    )
+                    .with_system(
+                        toggle_controls_system
+                            .chain(button_label_system::<ControlsButton>)
+                            .run_if(on_button_interact::<ControlsButton>),
+                    )
+                    .with_system(
+                        toggle_fire_mode_system
+                            .chain(button_label_system::<FireModeButton>)
+                            .run_if(on_button_interact::<FireModeButton>),
+                    )
                     .into(),
             )
             // --- Basic button color changer ---
@@ -40,7 +64,11 @@ impl Plugin for PauseMenuPlugin {
     }
 }
 
-fn setup_pause_system(mut commands: Commands, ui_textures: Res<UiTextures>) {
+fn setup_pause_system(
+    mut commands: Commands,
+    ui_textures: Res<UiTextures>,
+    settings: Res<Settings>,
+) {
     commands
         .spawn_bundle(NodeBundle {
             style: Style {
@@ -57,7 +85,7 @@ fn setup_pause_system(mut commands: Commands, ui_textures: Res<UiTextures>) {
             parent
                 .spawn_bundle(NodeBundle {
                     style: Style {
-                        size: Size::new(Val::Px(150.), Val::Px(130.)),
+                        size: Size::new(Val::Px(220.), Val::Px(390.)),
                         border: Rect::all(Val::Px(2.)),
                         ..Default::default()
                     },
@@ -78,61 +106,43 @@ fn setup_pause_system(mut commands: Commands, ui_textures: Res<UiTextures>) {
                             ..Default::default()
                         })
                         .with_children(|parent| {
-                            parent
-                                .spawn_bundle(ButtonBundle {
-                                    style: Style {
-                                        size: Size::new(Val::Percent(100.0), Val::Percent(50.0)),
-                                        margin: Rect::all(Val::Auto),
-                                        justify_content: JustifyContent::Center,
-                                        align_items: AlignItems::Center,
-                                        ..Default::default()
-                                    },
-                                    color: NORMAL_BUTTON.into(),
-                                    ..Default::default()
-                                })
-                                .with_children(|parent| {
-                                    parent.spawn_bundle(TextBundle {
-                                        text: Text::with_section(
-                                            "Exit",
-                                            TextStyle {
-                                                font: ui_textures.ui_font.clone(),
-                                                font_size: 40.0,
-                                                color: Color::rgb(0.9, 0.9, 0.9),
-                                            },
-                                            Default::default(),
-                                        ),
-                                        ..default()
-                                    });
-                                })
-                                .insert(ExitGameButton);
-
-                            parent
-                                .spawn_bundle(ButtonBundle {
-                                    style: Style {
-                                        size: Size::new(Val::Percent(100.0), Val::Percent(50.0)),
-                                        margin: Rect::all(Val::Auto),
-                                        justify_content: JustifyContent::Center,
-                                        align_items: AlignItems::Center,
-                                        ..Default::default()
-                                    },
-                                    color: NORMAL_BUTTON.into(),
-                                    ..Default::default()
-                                })
-                                .with_children(|parent| {
-                                    parent.spawn_bundle(TextBundle {
-                                        text: Text::with_section(
-                                            "Resume game",
-                                            TextStyle {
-                                                font: ui_textures.ui_font.clone(),
-                                                font_size: 40.0,
-                                                color: Color::rgb(0.9, 0.9, 0.9),
-                                            },
-                                            Default::default(),
-                                        ),
-                                        ..default()
-                                    });
-                                })
-                                .insert(ResumeGameButton);
+                            let height = Val::Percent(16.0);
+                            spawn_menu_button(parent, &ui_textures, "Exit", height, ExitGameButton);
+                            spawn_menu_button(
+                                parent,
+                                &ui_textures,
+                                director_label(&settings),
+                                height,
+                                DirectorButton,
+                            );
+                            spawn_menu_button(
+                                parent,
+                                &ui_textures,
+                                screen_shake_label(&settings),
+                                height,
+                                ScreenShakeButton,
+                            );
+                            spawn_menu_button(
+                                parent,
+                                &ui_textures,
+                                controls_label(&settings),
+                                height,
+                                ControlsButton,
+                            );
+                            spawn_menu_button(
+                                parent,
+                                &ui_textures,
+                                fire_mode_label(&settings),
+                                height,
+                                FireModeButton,
+                            );
+                            spawn_menu_button(
+                                parent,
+                                &ui_textures,
+                                "Resume game",
+                                height,
+                                ResumeGameButton,
+                            );
                         });
                 });
         })
@@ -142,3 +152,57 @@ fn setup_pause_system(mut commands: Commands, ui_textures: Res<UiTextures>) {
 fn resume_gameplay_system(mut commands: Commands) {
     commands.insert_resource(NextState(AppState::Gameplay));
 }
+
+fn screen_shake_label(settings: &Settings) -> String {
+    if settings.screen_shake {
+        "Shake: On".to_string()
+    } else {
+        "Shake: Off".to_string()
+    }
+}
+
+fn toggle_screen_shake_system(mut settings: ResMut<Settings>) -> String {
+    settings.screen_shake = !settings.screen_shake;
+    screen_shake_label(&settings)
+}
+
+fn director_label(settings: &Settings) -> String {
+    if settings.adaptive_difficulty {
+        "Adaptive: On".to_string()
+    } else {
+        "Adaptive: Off".to_string()
+    }
+}
+
+fn toggle_director_system(mut settings: ResMut<Settings>) -> String {
+    settings.adaptive_difficulty = !settings.adaptive_difficulty;
+    director_label(&settings)
+}
+
+fn controls_label(settings: &Settings) -> String {
+    match settings.controls {
+        ControlScheme::Keyboard => "Input: Keys".to_string(),
+        ControlScheme::Pointer => "Input: Mouse".to_string(),
+    }
+}
+
+fn toggle_controls_system(mut settings: ResMut<Settings>) -> String {
+    settings.controls = match settings.controls {
+        ControlScheme::Keyboard => ControlScheme::Pointer,
+        ControlScheme::Pointer => ControlScheme::Keyboard,
+    };
+    controls_label(&settings)
+}
+
+fn fire_mode_label(settings: &Settings) -> String {
+    match settings.fire_mode {
+        FireMode::Tap => "Fire: Tap".to_string(),
+        FireMode::Hold => "Fire: Hold".to_string(),
+        FireMode::Auto => "Fire: Auto".to_string(),
+    }
+}
+
+fn toggle_fire_mode_system(mut settings: ResMut<Settings>) -> String {
+    settings.fire_mode = settings.fire_mode.next();
+    fire_mode_label(&settings)
+}
@@ -5,3 +5,6 @@ pub struct GameOverMenu;
 
 #[derive(Component)]
 pub struct RespawnButton;
+
+#[derive(Component)]
+pub struct ContinueButton;
@@ -5,13 +5,12 @@ use iyes_loopless::{
 };
 
 use crate::shared::{
-    components::{ExitGameButton, GameRunning, ResetGameplay, SpawnPlayer},
-    constants::NORMAL_BUTTON,
-    general::{button_color_system, despawn_system, on_button_interact},
-    resources::{AppState, UiTextures},
+    components::{ContinueGameplay, ExitGameButton, GameRunning, ResetGameplay, SpawnPlayer},
+    general::{button_color_system, despawn_system, on_button_interact, spawn_menu_button},
+    resources::{AppState, Continues, UiTextures},
 };
 
-use super::components::{GameOverMenu, RespawnButton};
+use super::components::{ContinueButton, GameOverMenu, RespawnButton};
 
 pub struct GameOverPlugin;
 
@@ -23,6 +22,7 @@ impl Plugin for GameOverPlugin {
                 ConditionSet::new()
                     .run_in_state(AppState::GameOver)
                     .with_system(respawn_system.run_if(on_button_interact::<RespawnButton>))
+                    .with_system(continue_system.run_if(on_button_interact::<ContinueButton>))
                     .into(),
             )
             // --- Basic button color changer ---
@@ -37,7 +37,13 @@ impl Plugin for GameOverPlugin {
     }
 }
 
-fn setup_gameover_system(mut commands: Commands, ui_textures: Res<UiTextures>) {
+fn setup_gameover_system(
+    mut commands: Commands,
+    ui_textures: Res<UiTextures>,
+    continues: Option<Res<Continues>>,
+) {
+    let continues_left = continues.map_or(0, |continues| continues.0);
+
     commands
         .spawn_bundle(NodeBundle {
             style: Style {
@@ -54,7 +60,7 @@ fn setup_gameover_system(mut commands: Commands, ui_textures: Res<UiTextures>) {
             parent
                 .spawn_bundle(NodeBundle {
                     style: Style {
-                        size: Size::new(Val::Px(180.), Val::Px(200.)),
+                        size: Size::new(Val::Px(220.), Val::Px(260.)),
                         border: Rect::all(Val::Px(2.)),
                         ..Default::default()
                     },
@@ -75,61 +81,26 @@ fn setup_gameover_system(mut commands: Commands, ui_textures: Res<UiTextures>) {
                             ..Default::default()
                         })
                         .with_children(|parent| {
-                            parent
-                                .spawn_bundle(ButtonBundle {
-                                    style: Style {
-                                        size: Size::new(Val::Percent(100.0), Val::Percent(30.0)),
-                                        margin: Rect::all(Val::Auto),
-                                        justify_content: JustifyContent::Center,
-                                        align_items: AlignItems::Center,
-                                        ..Default::default()
-                                    },
-                                    color: NORMAL_BUTTON.into(),
-                                    ..Default::default()
-                                })
-                                .with_children(|parent| {
-                                    parent.spawn_bundle(TextBundle {
-                                        text: Text::with_section(
-                                            "Exit",
-                                            TextStyle {
-                                                font: ui_textures.ui_font.clone(),
-                                                font_size: 40.0,
-                                                color: Color::rgb(0.9, 0.9, 0.9),
-                                            },
-                                            Default::default(),
-                                        ),
-                                        ..default()
-                                    });
-                                })
-                                .insert(ExitGameButton);
+                            let height = Val::Percent(20.0);
+                            spawn_menu_button(parent, &ui_textures, "Exit", height, ExitGameButton);
+                            spawn_menu_button(
+                                parent,
+                                &ui_textures,
+                                "Respawn",
+                                height,
+                                RespawnButton,
+                            );
 
-                            parent
-                                .spawn_bundle(ButtonBundle {
-                                    style: Style {
-                                        size: Size::new(Val::Percent(100.0), Val::Percent(30.0)),
-                                        margin: Rect::all(Val::Auto),
-                                        justify_content: JustifyContent::Center,
-                                        align_items: AlignItems::Center,
-                                        ..Default::default()
-                                    },
-                                    color: NORMAL_BUTTON.into(),
-                                    ..Default::default()
-                                })
-                                .with_children(|parent| {
-                                    parent.spawn_bundle(TextBundle {
-                                        text: Text::with_section(
-                                            "Respawn",
-                                            TextStyle {
-                                                font: ui_textures.ui_font.clone(),
-                                                font_size: 40.0,
-                                                color: Color::rgb(0.9, 0.9, 0.9),
-                                            },
-                                            Default::default(),
-                                        ),
-                                        ..default()
-                                    });
-                                })
-                                .insert(RespawnButton);
+                            // continuing is only offered while the run has some left
+                            if continues_left > 0 {
+                                spawn_menu_button(
+                                    parent,
+                                    &ui_textures,
+                                    format!("Continue {}", continues_left),
+                                    height,
+                                    ContinueButton,
+                                );
+                            }
 
                             parent
                                 .spawn_bundle(NodeBundle {
@@ -165,3 +136,9 @@ fn respawn_system(mut commands: Commands) {
     commands.insert_resource(NextState(AppState::Gameplay));
     commands.insert_resource(SpawnPlayer);
 }
+
+fn continue_system(mut commands: Commands) {
+    commands.insert_resource(ContinueGameplay);
+    commands.insert_resource(NextState(AppState::Gameplay));
+    commands.insert_resource(SpawnPlayer);
+}